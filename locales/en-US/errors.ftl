@@ -0,0 +1,9 @@
+ER-CART-LIMIT = { $count ->
+        [one] { $count } item is over the cart limit of { $limit }
+       *[other] { $count } items are over the cart limit of { $limit }
+    }
+    .title = Cart limit exceeded
+    .hint = Remove some items and try again
+
+ER-0008 = User { $user_id } not found
+    .title = User not found
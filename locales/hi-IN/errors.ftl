@@ -0,0 +1,6 @@
+ER-CART-LIMIT = { $count ->
+        [one] { $count } वस्तु कार्ट की सीमा { $limit } से अधिक है
+       *[other] { $count } वस्तुएँ कार्ट की सीमा { $limit } से अधिक हैं
+    }
+    .title = कार्ट की सीमा पार हो गई
+    .hint = कुछ वस्तुएँ हटाकर फिर से प्रयास करें
@@ -0,0 +1,210 @@
+//! Build-time helpers that turn the `errors.yaml` catalog, and the `ERROR_FTL_DIR` resources when
+//! set, into a typed `Code` enum.
+//!
+//! ```ignore
+//! // build.rs
+//! fn main() {
+//!     standard_error::codegen::generate_codes().expect("error generating error codes");
+//! }
+//!
+//! // src/lib.rs
+//! standard_error::include_codes!();
+//!
+//! fn foo() -> Result<(), StandardError> {
+//!     Err(StandardError::from_code(Code::Er0004))
+//! }
+//! ```
+use std::{collections::HashSet, env, fmt::Write, fs, path::Path};
+
+use serde_yaml::Value;
+use standard_error_catalog::ftl;
+
+use crate::loader::error_messages_from_yaml;
+use crate::locale::normalize_locale;
+use crate::StandardErrorMessages;
+
+pub const CODES_FILE_NAME: &str = "standard_error_codes.rs";
+
+/// Reads the catalog from `ERROR_YAML_FILE_PATH` (or `errors.yaml` next to the crate's `Cargo.toml`)
+/// and `ERROR_FTL_DIR`, and writes the generated enum to `$OUT_DIR/standard_error_codes.rs`
+pub fn generate_codes() -> Result<(), Box<dyn std::error::Error>> {
+    let yaml_path = match env::var("ERROR_YAML_FILE_PATH") {
+        Ok(path) => path.into(),
+        Err(_) => Path::new(&env::var("CARGO_MANIFEST_DIR")?).join("errors.yaml"),
+    };
+    let ftl_dir = env::var("ERROR_FTL_DIR").ok();
+    let out_path = Path::new(&env::var("OUT_DIR")?).join(CODES_FILE_NAME);
+    println!("cargo:rerun-if-changed={}", yaml_path.display());
+    println!("cargo:rerun-if-env-changed=ERROR_YAML_FILE_PATH");
+    println!("cargo:rerun-if-env-changed=ERROR_FTL_DIR");
+    let Some(ftl_dir) = ftl_dir else {
+        return generate_codes_from(&yaml_path, &out_path);
+    };
+    println!("cargo:rerun-if-changed={}", ftl_dir);
+    // a fluent only catalog doesn't need the yaml
+    let yaml: Value = match yaml_path.exists() {
+        true => serde_yaml::from_str(&fs::read_to_string(&yaml_path)?)?,
+        false => Value::Null,
+    };
+    let messages = catalog_messages(&yaml, Some(Path::new(&ftl_dir)))?;
+    fs::write(out_path, render_messages(&messages)?)?;
+    Ok(())
+}
+
+pub fn generate_codes_from(
+    yaml_path: &Path,
+    out_path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let yaml: Value = serde_yaml::from_str(&fs::read_to_string(yaml_path)?)?;
+    fs::write(out_path, render_codes(&yaml)?)?;
+    Ok(())
+}
+
+pub fn render_codes(yaml: &Value) -> Result<String, Box<dyn std::error::Error>> {
+    render_messages(&catalog_messages(yaml, None)?)
+}
+
+/// The yaml messages with those of `<ftl_dir>/<locale>/errors.ftl` on top, ftl wins like at runtime
+fn catalog_messages(yaml: &Value, ftl_dir: Option<&Path>) -> Result<StandardErrorMessages, Box<dyn std::error::Error>> {
+    let mut messages = error_messages_from_yaml(yaml);
+    if let Some(dir) = ftl_dir {
+        for (locale, _, resource) in ftl::ftl_catalog(dir)? {
+            for message in resource {
+                messages
+                    .entry(message.id)
+                    .or_default()
+                    .insert(normalize_locale(&locale), message.text);
+            }
+        }
+    }
+    Ok(messages)
+}
+
+fn render_messages(messages: &StandardErrorMessages) -> Result<String, Box<dyn std::error::Error>> {
+    let mut codes: Vec<&String> = messages.keys().collect();
+    codes.sort();
+
+    let mut variants = Vec::with_capacity(codes.len());
+    let mut seen = HashSet::new();
+    for code in codes {
+        let variant = variant_name(code);
+        if !seen.insert(variant.clone()) {
+            return Err(format!("error code {} collides with another code as {}", code, variant).into());
+        }
+        let doc = messages[code]
+            .get("en_US")
+            .map(|detail| format!("`{}`: {}", code, detail.replace('\n', " ")))
+            .unwrap_or_else(|| format!("`{}`", code));
+        variants.push((code, variant, doc));
+    }
+
+    let mut out = String::new();
+    writeln!(out, "// @generated by standard_error::codegen, do not edit")?;
+    writeln!(out, "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]")?;
+    writeln!(out, "pub enum Code {{")?;
+    for (_, variant, doc) in variants.iter() {
+        writeln!(out, "    #[doc = {:?}]", doc)?;
+        writeln!(out, "    {},", variant)?;
+    }
+    writeln!(out, "}}")?;
+    writeln!(out)?;
+    writeln!(out, "impl ::standard_error::ErrorCode for Code {{")?;
+    writeln!(out, "    fn as_str(&self) -> &'static str {{")?;
+    writeln!(out, "        match self {{")?;
+    for (code, variant, _) in variants.iter() {
+        writeln!(out, "            Code::{} => {:?},", variant, code)?;
+    }
+    writeln!(out, "        }}")?;
+    writeln!(out, "    }}")?;
+    writeln!(out, "}}")?;
+    Ok(out)
+}
+
+/// `ER-0004` -> `Er0004`, `ERR-GIT-BARE-REPO` -> `ErrGitBareRepo`
+fn variant_name(code: &str) -> String {
+    let mut name: String = code
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let lower = part.to_ascii_lowercase();
+            let mut chars = lower.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect();
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        name.insert(0, 'E');
+    }
+    name
+}
+
+#[macro_export]
+macro_rules! include_codes {
+    () => {
+        include!(concat!(env!("OUT_DIR"), "/standard_error_codes.rs"));
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_variant_name() {
+        assert_eq!(variant_name("ER-0004"), "Er0004");
+        assert_eq!(variant_name("ERR-GIT-BARE-REPO"), "ErrGitBareRepo");
+        assert_eq!(variant_name("404-NOTFOUND"), "E404Notfound");
+    }
+
+    #[test]
+    fn test_render_codes() {
+        let yaml: Value = serde_yaml::from_str(
+            r#"
+errors:
+  - code: ER-0004
+    detail_en_US: "Should be an integer"
+  - code: ER-ORDER-NOTFOUND
+    detail_en_US: "Order [id] not found"
+"#,
+        )
+        .unwrap();
+        let out = render_codes(&yaml).unwrap();
+        assert!(out.contains("#[doc = \"`ER-0004`: Should be an integer\"]\n    Er0004,"));
+        assert!(out.contains("Code::ErOrderNotfound => \"ER-ORDER-NOTFOUND\","));
+        // built-in codes are always there
+        assert!(out.contains("Code::ErIoNotfound => \"ER-IO-NOTFOUND\","));
+    }
+
+    #[test]
+    fn test_render_codes_collision() {
+        let yaml: Value = serde_yaml::from_str(
+            r#"
+errors:
+  - code: ER-0004
+    detail_en_US: "Should be an integer"
+  - code: ER_0004
+    detail_en_US: "Should be an integer"
+"#,
+        )
+        .unwrap();
+        assert!(render_codes(&yaml).is_err());
+    }
+
+    #[test]
+    fn test_render_codes_ftl() {
+        let yaml: Value = serde_yaml::from_str(
+            r#"
+errors:
+  - code: ER-0008
+    detail_en_US: "User [user_id] not found"
+"#,
+        )
+        .unwrap();
+        let messages = catalog_messages(&yaml, Some(Path::new("locales"))).unwrap();
+        let out = render_messages(&messages).unwrap();
+        assert!(out.contains("Code::ErCartLimit => \"ER-CART-LIMIT\","));
+        assert!(out.contains("#[doc = \"`ER-0008`: User { $user_id } not found\"]\n    Er0008,"));
+    }
+}
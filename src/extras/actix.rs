@@ -0,0 +1,142 @@
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderValue, ACCEPT, ACCEPT_LANGUAGE, CONTENT_LANGUAGE, CONTENT_TYPE, VARY};
+use actix_web::http::StatusCode;
+use actix_web::middleware::Next;
+use actix_web::{HttpResponse, ResponseError};
+
+use crate::extras::formatter::{
+    error_body, errors_body, negotiated_error_body, negotiated_errors_body, ResponseContext,
+};
+use crate::extras::negotiate::Representation;
+use crate::locale::{negotiate_locale, with_locale};
+use crate::{settings, Metadata, StandardError, StandardErrors};
+
+/// actix-web is still on `http` 0.2, so the status goes over as a number
+fn actix_status(status: http::StatusCode) -> StatusCode {
+    StatusCode::from_u16(status.as_u16()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+fn response(status: StatusCode, (content_type, body): (&'static str, String)) -> HttpResponse {
+    HttpResponse::build(status).content_type(content_type).body(body)
+}
+
+impl ResponseError for StandardError {
+    fn status_code(&self) -> StatusCode {
+        actix_status(self.status_code)
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        response(self.status_code(), error_body(self))
+    }
+}
+
+impl ResponseError for StandardErrors {
+    fn status_code(&self) -> StatusCode {
+        actix_status(self.status())
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        response(self.status_code(), errors_body(self))
+    }
+}
+
+/// actix-web's `finalize_errors`: runs the handler in the `Accept-Language` locale and re-renders
+/// error responses with the request id, the path as the problem `instance` and the representation
+/// asked for in `Accept`, like the axum middleware and `LocaleLayer` do.
+/// Add it with `.wrap(actix_web::middleware::from_fn(standard_error::finalize_actix_errors))`
+pub async fn finalize_actix_errors(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, actix_web::Error> {
+    let header = |name| req.headers().get(name).and_then(|v: &HeaderValue| v.to_str().ok());
+    let request_id = header(settings.request_id_header.as_str()).map(|v| v.to_string());
+    let representation = header(ACCEPT.as_str())
+        .map(Representation::from_accept)
+        .unwrap_or(Representation::Json);
+    let locale = header(ACCEPT_LANGUAGE.as_str())
+        .and_then(negotiate_locale)
+        .unwrap_or_else(|| settings.default_locale.clone());
+    let path = req.path().to_string();
+    let response = with_locale(locale.clone(), next.call(req)).await?.map_into_boxed_body();
+    if !(response.status().is_client_error() || response.status().is_server_error()) {
+        return Ok(response);
+    }
+
+    let ctx = ResponseContext { locale, request_id };
+    let error = response.response().error();
+    let body = match (
+        error.and_then(|e| e.as_error::<StandardError>()),
+        error.and_then(|e| e.as_error::<StandardErrors>()),
+    ) {
+        (Some(err), _) => {
+            let err = match err.instance_uri() {
+                Some(_) => err.clone(),
+                None => err.clone().instance(&path),
+            };
+            Some(negotiated_error_body(&err, representation, &ctx))
+        }
+        (None, Some(errs)) => Some(negotiated_errors_body(errs, representation, &ctx)),
+        (None, None) => None,
+    };
+    let (req, mut res) = response.into_parts();
+    if let Some((content_type, body)) = body {
+        res = res.set_body(BoxBody::new(body));
+        res.headers_mut().insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+        res.headers_mut().append(VARY, HeaderValue::from_static("accept"));
+    }
+    res.headers_mut().append(VARY, HeaderValue::from_static("accept-language"));
+    if let Ok(language) = HeaderValue::from_str(&ctx.locale.replace('_', "-")) {
+        res.headers_mut().insert(CONTENT_LANGUAGE, language);
+    }
+    Ok(ServiceResponse::new(req, res))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Interpolate;
+    use actix_web::body::to_bytes;
+    use actix_web::test::{call_service, init_service, TestRequest};
+    use actix_web::{web, App};
+    use serde_json::{json, Value};
+
+    #[tokio::test]
+    async fn test_actix_response() {
+        let err = StandardError::new("ER-0008")
+            .interpolate_values([("user_id".to_string(), "42".to_string())].into());
+        let response = err.error_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers().get("content-type").unwrap(), "application/json");
+        let body = to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(serde_json::from_slice::<Value>(&body).unwrap(), json!({"detail": "User 42 not found"}));
+
+        let errs: StandardErrors = vec![StandardError::new("ER-0004"), StandardError::new("ER-0008")]
+            .into_iter()
+            .collect();
+        assert_eq!(errs.error_response().status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[tokio::test]
+    async fn test_finalize_actix_errors() {
+        let app = init_service(
+            App::new()
+                .wrap(actix_web::middleware::from_fn(finalize_actix_errors))
+                .route("/users/42", web::get().to(|| async { Err::<HttpResponse, _>(StandardError::new("ER-0008")) })),
+        )
+        .await;
+        let req = TestRequest::get()
+            .uri("/users/42")
+            .insert_header(("accept", "text/plain"))
+            .insert_header(("accept-language", "hi-IN"))
+            .to_request();
+        let response = call_service(&app, req).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers().get(CONTENT_TYPE).unwrap(), "text/plain; charset=utf-8");
+        assert_eq!(response.headers().get(CONTENT_LANGUAGE).unwrap(), "hi-IN");
+        let vary: Vec<_> = response.headers().get_all(VARY).collect();
+        assert_eq!(vary, ["accept", "accept-language"]);
+        let body = to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(body, "404 ER-0008: उपयोगकर्ता [user_id] नहीं मिला\n");
+    }
+}
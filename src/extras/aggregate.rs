@@ -0,0 +1,190 @@
+use std::{fmt, slice};
+
+use http::StatusCode;
+use serde_json::{json, Value};
+
+use crate::StandardError;
+
+/// One entry of a `StandardErrors`, `pointer` is a JSON pointer to the offending field, e.g. `/items/3/email`
+#[derive(Debug, Clone)]
+pub struct ErrorItem {
+    pub error: StandardError,
+    pub pointer: Option<String>,
+}
+
+/// How a `StandardErrors` picks the status of its response
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StatusRule {
+    /// the numerically highest status of all items, so a 500 wins over a 422
+    #[default]
+    Highest,
+    /// the status of the first item
+    First,
+    /// the items' status if they all agree, the given one otherwise
+    Uniform(StatusCode),
+    Fixed(StatusCode),
+}
+
+/// Collects several errors so form and bulk endpoints can report every problem at once
+#[derive(Debug, Clone, Default)]
+pub struct StandardErrors {
+    items: Vec<ErrorItem>,
+    status_rule: StatusRule,
+}
+
+impl StandardErrors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn status_rule(mut self, rule: StatusRule) -> Self {
+        self.status_rule = rule;
+        self
+    }
+
+    pub fn push(&mut self, error: StandardError) {
+        self.items.push(ErrorItem { error, pointer: None });
+    }
+
+    pub fn push_at(&mut self, pointer: impl Into<String>, error: StandardError) {
+        self.items.push(ErrorItem { error, pointer: Some(pointer.into()) });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn iter(&self) -> slice::Iter<'_, ErrorItem> {
+        self.items.iter()
+    }
+
+    /// `Ok(())` when nothing was collected, so a handler can end its checks with `errors.into_result()?`
+    pub fn into_result(self) -> Result<(), StandardErrors> {
+        match self.is_empty() {
+            true => Ok(()),
+            false => Err(self),
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        let mut statuses = self.items.iter().map(|item| item.error.status_code);
+        match self.status_rule {
+            StatusRule::Highest => statuses.max(),
+            StatusRule::First => statuses.next(),
+            StatusRule::Uniform(fallback) => statuses
+                .next()
+                .map(|first| match statuses.all(|status| status == first) {
+                    true => first,
+                    false => fallback,
+                }),
+            StatusRule::Fixed(status) => Some(status),
+        }
+        .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    pub(crate) fn body(&self, locale: &str) -> Value {
+        let errors: Vec<Value> = self
+            .items
+            .iter()
+            .map(|item| {
+                let mut error = json!({"code": item.error.err_code, "detail": item.error.render(locale)});
+                if let Some(pointer) = &item.pointer {
+                    error["pointer"] = json!(pointer);
+                }
+                error
+            })
+            .collect();
+        json!({"errors": errors})
+    }
+}
+
+impl From<StandardError> for StandardErrors {
+    fn from(error: StandardError) -> Self {
+        let mut errors = StandardErrors::new();
+        errors.push(error);
+        errors
+    }
+}
+
+impl Extend<StandardError> for StandardErrors {
+    fn extend<I: IntoIterator<Item = StandardError>>(&mut self, iter: I) {
+        self.items.extend(iter.into_iter().map(|error| ErrorItem { error, pointer: None }));
+    }
+}
+
+impl Extend<ErrorItem> for StandardErrors {
+    fn extend<I: IntoIterator<Item = ErrorItem>>(&mut self, iter: I) {
+        self.items.extend(iter);
+    }
+}
+
+impl FromIterator<StandardError> for StandardErrors {
+    fn from_iter<I: IntoIterator<Item = StandardError>>(iter: I) -> Self {
+        let mut errors = StandardErrors::new();
+        errors.extend(iter);
+        errors
+    }
+}
+
+impl IntoIterator for StandardErrors {
+    type Item = ErrorItem;
+    type IntoIter = std::vec::IntoIter<ErrorItem>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
+
+impl fmt::Display for StandardErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let codes: Vec<&str> = self.items.iter().map(|item| item.error.err_code.as_ref()).collect();
+        write!(f, "{} errors with status {}: {}", self.items.len(), self.status(), codes.join(", "))
+    }
+}
+
+impl std::error::Error for StandardErrors {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Interpolate, Status};
+
+    #[test]
+    fn test_status_rule() {
+        let mut errors = StandardErrors::new();
+        errors.push(StandardError::new("ER-0004").code(StatusCode::UNPROCESSABLE_ENTITY));
+        errors.push_at("/user_id", StandardError::new("ER-0008"));
+        assert_eq!(errors.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let errors = errors.status_rule(StatusRule::First);
+        assert_eq!(errors.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let errors = errors.status_rule(StatusRule::Uniform(StatusCode::BAD_REQUEST));
+        assert_eq!(errors.status(), StatusCode::BAD_REQUEST);
+        let errors = errors.status_rule(StatusRule::Fixed(StatusCode::IM_A_TEAPOT));
+        assert_eq!(errors.status(), StatusCode::IM_A_TEAPOT);
+    }
+
+    #[test]
+    fn test_into_result() {
+        assert!(StandardErrors::new().into_result().is_ok());
+
+        let mut errors: StandardErrors = vec![StandardError::new("ER-0004")].into_iter().collect();
+        errors.push_at(
+            "/items/3/user_id",
+            StandardError::new("ER-0008").interpolate_values([("user_id".to_string(), "3".to_string())].into()),
+        );
+        let errors = errors.into_result().unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(
+            errors.body("en_US"),
+            json!({"errors": [
+                {"code": "ER-0004", "detail": "Should be an integer"},
+                {"code": "ER-0008", "detail": "User 3 not found", "pointer": "/items/3/user_id"},
+            ]})
+        );
+    }
+}
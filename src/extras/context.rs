@@ -0,0 +1,82 @@
+use std::borrow::Cow;
+use std::error::Error;
+
+use crate::{error_metadata, Interpolate, Source, StandardError};
+
+pub trait ResultExt<T, E> {
+    fn std_err(self, code: impl Into<Cow<'static, str>>) -> Result<T, StandardError>;
+    fn std_err_with<F>(self, f: F) -> Result<T, StandardError>
+    where
+        F: FnOnce(&E) -> StandardError;
+}
+
+impl<T, E> ResultExt<T, E> for Result<T, E>
+where
+    E: Error + Send + Sync + 'static,
+{
+    fn std_err(self, code: impl Into<Cow<'static, str>>) -> Result<T, StandardError> {
+        self.map_err(|e| {
+            StandardError::new(code)
+                .interpolate_err(e.to_string())
+                .with_source(e)
+        })
+    }
+
+    fn std_err_with<F>(self, f: F) -> Result<T, StandardError>
+    where
+        F: FnOnce(&E) -> StandardError,
+    {
+        self.map_err(|e| {
+            let err = f(&e);
+            match err.source {
+                Some(_) => err,
+                None => err.with_source(e),
+            }
+        })
+    }
+}
+
+pub trait OptionExt<T> {
+    fn ok_or_std(self, code: impl Into<Cow<'static, str>>) -> Result<T, StandardError>;
+}
+
+impl<T> OptionExt<T> for Option<T> {
+    fn ok_or_std(self, code: impl Into<Cow<'static, str>>) -> Result<T, StandardError> {
+        self.ok_or_else(|| StandardError::new(code))
+    }
+}
+
+pub trait Context<T> {
+    fn context(self, code: impl Into<Cow<'static, str>>) -> Result<T, StandardError>;
+}
+
+impl<T> Context<T> for Result<T, StandardError> {
+    fn context(self, code: impl Into<Cow<'static, str>>) -> Result<T, StandardError> {
+        self.map_err(|e| e.context(code))
+    }
+}
+
+impl StandardError {
+    /// Wraps this error in an outer `code`, keeping it as the cause. Unless the outer code
+    /// has a status of its own in the catalog, the inner status is kept
+    pub fn context(self, code: impl Into<Cow<'static, str>>) -> StandardError {
+        let mut outer = StandardError::new(code);
+        if error_metadata.get(outer.err_code.as_ref()).and_then(|m| m.status).is_none() {
+            outer.status_code = self.status_code;
+        }
+        outer.with_source(self)
+    }
+
+    /// The `StandardError`s this one was built on top of, innermost last
+    pub fn causes(&self) -> Vec<&StandardError> {
+        let mut causes = Vec::new();
+        let mut current = self.source.as_deref().map(|e| e as &(dyn Error + 'static));
+        while let Some(e) = current {
+            if let Some(standard) = e.downcast_ref::<StandardError>() {
+                causes.push(standard);
+            }
+            current = e.source();
+        }
+        causes
+    }
+}
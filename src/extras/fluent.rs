@@ -0,0 +1,215 @@
+//! Project Fluent catalogs, `<ERROR_FTL_DIR>/<locale>/errors.ftl`. Message ids are error codes,
+//! the `.title` and `.hint` attributes fill the metadata and the values are passed as `FluentArgs`
+use std::{collections::HashMap, error::Error, fs, path::Path, sync::Arc};
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource, FluentValue};
+use fluent_syntax::ast;
+use lazy_static::lazy_static;
+use unic_langid::LanguageIdentifier;
+
+use crate::extras::interpolate::{render_template, TemplateSyntax};
+use crate::locale::normalize_locale;
+use crate::{settings, StandardErrorMessages, StandardErrorMetadata};
+
+/// Numbers only when they print back the same, so `007`, long ids and `nan` stay text. Plural
+/// selectors need numbers, `$count` values like `3` or `2.5` still are
+fn fluent_value(value: &str) -> FluentValue<'_> {
+    let unsigned = value.strip_prefix('-').unwrap_or(value);
+    let (int, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    let digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    let exact = !int.is_empty()
+        && digits(int)
+        && digits(fraction)
+        && (int == "0" || !int.starts_with('0'))
+        && fraction.is_empty() != unsigned.contains('.')
+        // f64 keeps 15 significant digits
+        && int.len() + fraction.len() <= 15;
+    match exact {
+        true => FluentValue::try_number(value),
+        false => FluentValue::String(value.into()),
+    }
+}
+
+pub struct FluentCatalog {
+    bundles: HashMap<String, FluentBundle<FluentResource>>,
+    /// message ids of each locale, in file order
+    ids: HashMap<String, Vec<String>>,
+}
+
+impl FluentCatalog {
+    /// Reads every `<dir>/<locale>/errors.ftl`, directory names are normalized like the yaml keys
+    pub fn load(dir: &Path) -> Result<Self, Box<dyn Error>> {
+        let mut catalog = FluentCatalog { bundles: HashMap::new(), ids: HashMap::new() };
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = entry.path().join("errors.ftl");
+            if !path.is_file() {
+                continue;
+            }
+            let locale = normalize_locale(&entry.file_name().to_string_lossy());
+            let langid: LanguageIdentifier = locale.replace('_', "-").parse()?;
+            let resource = FluentResource::try_new(fs::read_to_string(&path)?)
+                .map_err(|(_, errors)| format!("{}: {:?}", path.display(), errors))?;
+            let ids = resource
+                .entries()
+                .filter_map(|entry| match entry {
+                    ast::Entry::Message(message) => Some(message.id.name.to_string()),
+                    _ => None,
+                })
+                .collect();
+            let mut bundle = FluentBundle::new_concurrent(vec![langid]);
+            // no unicode isolation marks around placeables, messages end up in json and logs
+            bundle.set_use_isolating(false);
+            bundle
+                .add_resource(resource)
+                .map_err(|errors| format!("{}: {:?}", path.display(), errors))?;
+            catalog.bundles.insert(locale.clone(), bundle);
+            catalog.ids.insert(locale, ids);
+        }
+        Ok(catalog)
+    }
+
+    fn format(&self, code: &str, locale: &str, attribute: Option<&str>, values: &HashMap<String, String>) -> Option<String> {
+        let bundle = self.bundles.get(locale)?;
+        let message = bundle.get_message(code)?;
+        let pattern = match attribute {
+            Some(name) => message.get_attribute(name)?.value(),
+            None => message.value()?,
+        };
+        let mut args = FluentArgs::new();
+        for (name, value) in values {
+            args.set(name.as_str(), fluent_value(value));
+        }
+        let mut errors = Vec::new();
+        let formatted = bundle.format_pattern(pattern, Some(&args), &mut errors);
+        if !errors.is_empty() {
+            log::debug!("formatting {} in {}: {:?}", code, locale, errors);
+        }
+        Some(formatted.into_owned())
+    }
+
+    /// The message of `code` in `locale`, `None` if that bundle doesn't have it
+    pub fn render(&self, code: &str, locale: &str, values: &HashMap<String, String>) -> Option<String> {
+        self.format(code, locale, None, values)
+    }
+
+    /// `code -> locale -> text` of an attribute, formatted without values
+    fn attributes(&self, name: &str) -> HashMap<String, HashMap<String, String>> {
+        let mut found: HashMap<String, HashMap<String, String>> = HashMap::new();
+        for (locale, ids) in &self.ids {
+            for id in ids {
+                if let Some(text) = self.format(id, locale, Some(name), &HashMap::new()) {
+                    found.entry(id.clone()).or_default().insert(locale.clone(), text);
+                }
+            }
+        }
+        found
+    }
+
+    /// Adds the messages to the catalog, a code in both the yaml and the ftl files uses the ftl one.
+    /// The stored text is the message formatted without values, it's what locale fallback sees
+    pub(crate) fn merge_messages(&self, messages: &mut StandardErrorMessages) {
+        for (locale, ids) in &self.ids {
+            for id in ids {
+                if let Some(text) = self.format(id, locale, None, &HashMap::new()) {
+                    messages.entry(id.clone()).or_default().insert(locale.clone(), text);
+                }
+            }
+        }
+    }
+
+    /// Marks the codes as fluent ones and fills their titles and hints, the rest of the yaml
+    /// metadata of a code (status, severity, ...) is kept
+    pub(crate) fn merge_metadata(&self, metadata: &mut StandardErrorMetadata) {
+        let titles = self.attributes("title");
+        let hints = self.attributes("hint");
+        for id in self.ids.values().flatten() {
+            let meta = Arc::make_mut(metadata.entry(id.clone()).or_default());
+            meta.syntax = TemplateSyntax::Fluent;
+            meta.titles.extend(titles.get(id).cloned().unwrap_or_default());
+            meta.hints.extend(hints.get(id).cloned().unwrap_or_default());
+        }
+    }
+}
+
+lazy_static! {
+    pub(crate) static ref fluent_catalog: Option<FluentCatalog> = settings
+        .error_ftl_dir
+        .as_ref()
+        .map(|dir| FluentCatalog::load(Path::new(dir)).expect("error loading fluent catalog"));
+}
+
+/// Renders through the bundle of `locale`, a locale only the yaml has is a `[placeholder]` template
+pub(crate) fn render(code: &str, template: &str, values: &HashMap<String, String>, locale: &str) -> String {
+    fluent_catalog
+        .as_ref()
+        .and_then(|catalog| catalog.render(code, locale, values))
+        .unwrap_or_else(|| render_template(template, values))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn catalog() -> FluentCatalog {
+        FluentCatalog::load(Path::new("locales")).unwrap()
+    }
+
+    fn values(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_render() {
+        let catalog = catalog();
+        let one = values(&[("count", "1"), ("limit", "10")]);
+        let many = values(&[("count", "3"), ("limit", "10")]);
+        assert_eq!(
+            catalog.render("ER-CART-LIMIT", "en_US", &one).unwrap(),
+            "1 item is over the cart limit of 10"
+        );
+        assert_eq!(
+            catalog.render("ER-CART-LIMIT", "en_US", &many).unwrap(),
+            "3 items are over the cart limit of 10"
+        );
+        assert_eq!(
+            catalog.render("ER-CART-LIMIT", "hi_IN", &many).unwrap(),
+            "3 वस्तुएँ कार्ट की सीमा 10 से अधिक हैं"
+        );
+        assert_eq!(catalog.render("ER-0008", "hi_IN", &values(&[])), None);
+        // ids aren't numbers, even when they look like one
+        assert_eq!(
+            catalog.render("ER-0008", "en_US", &values(&[("user_id", "007")])).unwrap(),
+            "User 007 not found"
+        );
+        assert_eq!(
+            catalog.render("ER-0008", "en_US", &values(&[("user_id", "12345678901234567890")])).unwrap(),
+            "User 12345678901234567890 not found"
+        );
+        assert_eq!(catalog.render("ER-0008", "en_US", &values(&[("user_id", "nan")])).unwrap(), "User nan not found");
+        let fraction = values(&[("count", "2.50"), ("limit", "-1")]);
+        assert_eq!(
+            catalog.render("ER-CART-LIMIT", "en_US", &fraction).unwrap(),
+            "2.50 items are over the cart limit of -1"
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        let catalog = catalog();
+        let mut messages = StandardErrorMessages::new();
+        messages.insert("ER-0008".to_string(), [("hi_IN".to_string(), "yaml".to_string())].into());
+        catalog.merge_messages(&mut messages);
+        assert_eq!(messages["ER-0008"]["en_US"], "User {$user_id} not found");
+        assert_eq!(messages["ER-0008"]["hi_IN"], "yaml");
+
+        let mut metadata = StandardErrorMetadata::new();
+        catalog.merge_metadata(&mut metadata);
+        let meta = &metadata["ER-CART-LIMIT"];
+        assert_eq!(meta.syntax, TemplateSyntax::Fluent);
+        assert_eq!(meta.titles["en_US"], "Cart limit exceeded");
+        assert_eq!(meta.hints["hi_IN"], "कुछ वस्तुएँ हटाकर फिर से प्रयास करें");
+        assert!(metadata["ER-0008"].hints.is_empty());
+    }
+}
@@ -0,0 +1,257 @@
+use std::sync::{Arc, RwLock};
+
+use lazy_static::lazy_static;
+use serde_json::{json, Map, Value};
+
+use crate::conf::ResponseStyle;
+#[cfg(any(feature = "axum", feature = "actix"))]
+use crate::extras::negotiate::Representation;
+use crate::extras::problem::PROBLEM_CONTENT_TYPE;
+use crate::{locale, settings, StandardError, StandardErrors};
+
+/// What a formatter gets to know about the response being built
+#[derive(Debug, Clone, Default)]
+pub struct ResponseContext {
+    pub locale: String,
+    /// only known when the error response went through `finalize_errors`
+    pub request_id: Option<String>,
+}
+
+impl ResponseContext {
+    pub fn current() -> Self {
+        ResponseContext {
+            locale: locale::get_current_locale(),
+            request_id: None,
+        }
+    }
+}
+
+/// Builds the JSON body of error responses, register your own with `set_response_formatter`
+pub trait ResponseFormatter: Send + Sync {
+    fn format(&self, err: &StandardError, ctx: &ResponseContext) -> Value;
+
+    fn format_many(&self, errs: &StandardErrors, ctx: &ResponseContext) -> Value {
+        errs.body(&ctx.locale)
+    }
+
+    fn content_type(&self) -> &'static str {
+        "application/json"
+    }
+}
+
+/// `{"detail": message}`, plus `causes` when the error wraps other `StandardError`s
+pub struct LegacyFormatter;
+
+impl ResponseFormatter for LegacyFormatter {
+    fn format(&self, err: &StandardError, ctx: &ResponseContext) -> Value {
+        let mut body = json!({"detail": err.render(&ctx.locale)});
+        let causes = err.causes();
+        if !causes.is_empty() {
+            body["causes"] = causes
+                .iter()
+                .map(|cause| json!({"code": cause.err_code, "detail": cause.render(&ctx.locale)}))
+                .collect();
+        }
+        body
+    }
+}
+
+pub struct ProblemFormatter;
+
+impl ResponseFormatter for ProblemFormatter {
+    fn format(&self, err: &StandardError, ctx: &ResponseContext) -> Value {
+        err.problem_details_in(&ctx.locale)
+    }
+
+    fn format_many(&self, errs: &StandardErrors, ctx: &ResponseContext) -> Value {
+        errs.problem_details_in(&ctx.locale)
+    }
+
+    fn content_type(&self) -> &'static str {
+        PROBLEM_CONTENT_TYPE
+    }
+}
+
+/// Fills a JSON template where string values like `"{code}"` are replaced by the matching field:
+/// `code`, `message`, `status`, `title`, `hint`, `type`, `values`, `causes`, `locale`, `request_id`
+/// and, for `StandardErrors` items, `pointer`. Fields without a value are left out
+///
+/// ```text
+/// RESPONSE_TEMPLATE='{"error": {"code": "{code}", "message": "{message}"}, "trace_id": "{request_id}"}'
+/// ```
+pub struct TemplateFormatter {
+    template: Value,
+}
+
+impl TemplateFormatter {
+    pub fn new(template: Value) -> Self {
+        TemplateFormatter { template }
+    }
+
+    fn field(err: &StandardError, name: &str, ctx: &ResponseContext, pointer: Option<&str>) -> Value {
+        match name {
+            "code" => json!(err.err_code),
+            "message" => json!(err.render(&ctx.locale)),
+            "status" => json!(err.status_code.as_u16()),
+            "title" => json!(err.title(&ctx.locale)),
+            "hint" => json!(err.hint(&ctx.locale)),
+            "type" => json!(err.problem_type()),
+            "values" => json!(err.values),
+            "causes" => err
+                .causes()
+                .iter()
+                .map(|cause| json!({"code": cause.err_code, "message": cause.render(&ctx.locale)}))
+                .collect(),
+            "locale" => json!(ctx.locale),
+            "request_id" => json!(ctx.request_id),
+            "pointer" => json!(pointer),
+            _ => Value::Null,
+        }
+    }
+
+    fn fill(template: &Value, lookup: &dyn Fn(&str) -> Value) -> Value {
+        match template {
+            Value::String(s) if s.starts_with('{') && s.ends_with('}') && s.len() > 2 => {
+                lookup(&s[1..s.len() - 1])
+            }
+            Value::Object(map) => {
+                let filled: Map<String, Value> = map
+                    .iter()
+                    .map(|(k, v)| (k.clone(), Self::fill(v, lookup)))
+                    .filter(|(_, v)| !v.is_null())
+                    .collect();
+                Value::Object(filled)
+            }
+            Value::Array(items) => items.iter().map(|v| Self::fill(v, lookup)).collect(),
+            other => other.clone(),
+        }
+    }
+}
+
+impl ResponseFormatter for TemplateFormatter {
+    fn format(&self, err: &StandardError, ctx: &ResponseContext) -> Value {
+        Self::fill(&self.template, &|name| Self::field(err, name, ctx, None))
+    }
+
+    fn format_many(&self, errs: &StandardErrors, ctx: &ResponseContext) -> Value {
+        let errors: Vec<Value> = errs
+            .iter()
+            .map(|item| {
+                Self::fill(&self.template, &|name| {
+                    Self::field(&item.error, name, ctx, item.pointer.as_deref())
+                })
+            })
+            .collect();
+        json!({"errors": errors})
+    }
+}
+
+lazy_static! {
+    static ref registered_formatter: RwLock<Option<Arc<dyn ResponseFormatter>>> = RwLock::new(None);
+    static ref configured_formatter: Arc<dyn ResponseFormatter> = match (&settings.response_template, settings.response_style) {
+        (Some(template), _) => Arc::new(TemplateFormatter::new(
+            serde_json::from_str(template).expect("RESPONSE_TEMPLATE should be valid json"),
+        )),
+        (None, ResponseStyle::Problem) => Arc::new(ProblemFormatter),
+        (None, ResponseStyle::Legacy) => Arc::new(LegacyFormatter),
+    };
+}
+
+/// Replaces the formatter picked from `Settings` for every error response
+pub fn set_response_formatter(formatter: impl ResponseFormatter + 'static) {
+    *registered_formatter.write().expect("response formatter lock poisoned") = Some(Arc::new(formatter));
+}
+
+pub fn response_formatter() -> Arc<dyn ResponseFormatter> {
+    match registered_formatter.read().expect("response formatter lock poisoned").as_ref() {
+        Some(formatter) => formatter.clone(),
+        None => configured_formatter.clone(),
+    }
+}
+
+/// Content type and body of an error response, shared by the framework integrations so they all
+/// answer with the same contract
+#[cfg(any(feature = "axum", feature = "actix"))]
+pub(crate) fn error_body(err: &StandardError) -> (&'static str, String) {
+    if !err.causes().is_empty() {
+        log::error!("{:#}", err);
+    }
+    let formatter = response_formatter();
+    (formatter.content_type(), formatter.format(err, &ResponseContext::current()).to_string())
+}
+
+#[cfg(any(feature = "axum", feature = "actix"))]
+pub(crate) fn errors_body(errs: &StandardErrors) -> (&'static str, String) {
+    let formatter = response_formatter();
+    (formatter.content_type(), formatter.format_many(errs, &ResponseContext::current()).to_string())
+}
+
+/// Body once the request is known, in the representation asked for in `Accept` or the
+/// formatter's json. Both `finalize_errors` middlewares answer with it
+#[cfg(any(feature = "axum", feature = "actix"))]
+pub(crate) fn negotiated_error_body(
+    err: &StandardError,
+    representation: Representation,
+    ctx: &ResponseContext,
+) -> (&'static str, String) {
+    match err.render_as(representation, &ctx.locale) {
+        Some(body) => (representation.content_type(), body),
+        None => {
+            let formatter = response_formatter();
+            (formatter.content_type(), formatter.format(err, ctx).to_string())
+        }
+    }
+}
+
+#[cfg(any(feature = "axum", feature = "actix"))]
+pub(crate) fn negotiated_errors_body(
+    errs: &StandardErrors,
+    representation: Representation,
+    ctx: &ResponseContext,
+) -> (&'static str, String) {
+    match errs.render_as(representation, &ctx.locale) {
+        Some(body) => (representation.content_type(), body),
+        None => {
+            let formatter = response_formatter();
+            (formatter.content_type(), formatter.format_many(errs, ctx).to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Context, Interpolate};
+
+    #[test]
+    fn test_template_formatter() {
+        let formatter = TemplateFormatter::new(json!({
+            "error": {"code": "{code}", "message": "{message}", "status": "{status}"},
+            "trace_id": "{request_id}",
+            "causes": "{causes}",
+            "service": "orders",
+        }));
+        let err = Err::<(), _>(StandardError::new("ER-0005").interpolate_err("abc".to_string()))
+            .context("ER-0008")
+            .unwrap_err();
+        let ctx = ResponseContext { locale: "en_US".to_string(), request_id: Some("req-1".to_string()) };
+        assert_eq!(
+            formatter.format(&err, &ctx),
+            json!({
+                "error": {"code": "ER-0008", "message": "User [user_id] not found", "status": 404},
+                "trace_id": "req-1",
+                "causes": [{"code": "ER-0005", "message": "Should be an integer: abc"}],
+                "service": "orders",
+            })
+        );
+
+        let ctx = ResponseContext { locale: "en_US".to_string(), request_id: None };
+        let mut errs = StandardErrors::new();
+        errs.push_at("/age", StandardError::new("ER-0004"));
+        let formatter = TemplateFormatter::new(json!({"code": "{code}", "field": "{pointer}", "trace_id": "{request_id}"}));
+        assert_eq!(
+            formatter.format_many(&errs, &ctx),
+            json!({"errors": [{"code": "ER-0004", "field": "/age"}]})
+        );
+    }
+}
@@ -1,7 +1,8 @@
 #[cfg(feature = "axum")]
-use axum::response::IntoResponse;
-use axum::http::{StatusCode, HeaderValue, Error as HttpError};
-use crate::{StandardError, Interpolate};
+use axum::http::{StatusCode, Error as HttpError};
+#[cfg(feature = "axum")]
+use crate::{StandardError, Interpolate, Source};
+#[cfg(feature = "axum")]
 use std::error::Error as StdError;
 
 #[cfg(feature = "axum")]
@@ -9,7 +10,7 @@ impl From<axum::Error> for StandardError {
     fn from(error: axum::Error) -> Self {
         log::error!("axum error: {}", &error.to_string());
 
-        if let Some(io_err) = error.source().and_then(|e| e.downcast_ref::<std::io::Error>()) {
+        let err = if let Some(io_err) = error.source().and_then(|e| e.downcast_ref::<std::io::Error>()) {
             StandardError::new("ER-AXUM-IO")
                 .interpolate_err(format!("IO error occurred: {}", io_err))
         } else if let Some(hyper_err) = error.source().and_then(|e| e.downcast_ref::<hyper::Error>()) {
@@ -21,7 +22,8 @@ impl From<axum::Error> for StandardError {
         } else {
             StandardError::new("ER-AXUM-UNKNOWN")
                 .interpolate_err("An unknown Axum error occurred".to_string())
-        }
+        };
+        err.with_source(error)
     }
 }
 
@@ -53,6 +55,7 @@ impl From<axum::http::header::InvalidHeaderValue> for StandardError {
         log::error!("Invalid header value error: {}", &error.to_string());
         StandardError::new("ER-AXUM-INVALIDHEADERVALUE")
             .interpolate_err("Invalid header value".to_string())
+            .with_source(error)
     }
 }
 
@@ -62,6 +65,7 @@ impl From<axum::http::header::InvalidHeaderName> for StandardError {
         log::error!("Invalid header name error: {}", &error.to_string());
         StandardError::new("ER-AXUM-INVALIDHEADERNAME")
             .interpolate_err("Invalid header name".to_string())
+            .with_source(error)
     }
 }
 
@@ -71,5 +75,6 @@ impl From<axum::http::Error> for StandardError {
         log::error!("HTTP error: {}", &error.to_string());
         StandardError::new("ER-AXUM-HTTPERROR")
             .interpolate_err("General HTTP error".to_string())
+            .with_source(error)
     }
 }
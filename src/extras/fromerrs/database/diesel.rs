@@ -1,13 +1,14 @@
 #[cfg(feature="diesel")]
 use diesel::result::Error as DieselError;
-use crate::{StandardError, Interpolate};
+#[cfg(feature="diesel")]
+use crate::{StandardError, Interpolate, Source};
 
 
 #[cfg(feature="diesel")]
 impl From<DieselError> for StandardError {
     fn from(error: DieselError) -> Self {
         log::error!("db error: {}", &error.to_string()); 
-        match error {
+        match &error {
             DieselError::NotFound => StandardError::new("ER-DB-NOTFOUND")
                 .interpolate_err("Record not found".to_string()),
             DieselError::DatabaseError(_, info) => StandardError::new("ER-DB-DATABASE")
@@ -23,6 +24,7 @@ impl From<DieselError> for StandardError {
             _ => StandardError::new("ER-DB-UNKNOWN")
                 .interpolate_err("An unknown Diesel error occurred".to_string()),
         }
+        .with_source(error)
     }
 }
 
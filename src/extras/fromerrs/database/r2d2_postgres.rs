@@ -1,4 +1,5 @@
-use crate::{StandardError, Interpolate};
+#[cfg(feature="diesel")]
+use crate::{StandardError, Interpolate, Source};
 #[cfg(feature="diesel")]
 use r2d2_postgres::r2d2::Error; 
 
@@ -6,6 +7,8 @@ use r2d2_postgres::r2d2::Error;
 #[cfg(feature="diesel")]
 impl From<Error> for StandardError {
     fn from(error: Error) -> Self {
-        StandardError::new("ER-DB-POOL").interpolate_err(error.to_string())
+        StandardError::new("ER-DB-POOL")
+            .interpolate_err(error.to_string())
+            .with_source(error)
     }
 }
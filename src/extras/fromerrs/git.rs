@@ -1,6 +1,6 @@
 
 #[cfg(feature="git")]
-use crate::{StandardError, Interpolate};
+use crate::{StandardError, Interpolate, Source};
 
 
 #[cfg(feature="git")]
@@ -36,6 +36,8 @@ impl From<git2::Error> for StandardError {
             git2::ErrorCode::Owner => "ERR-GIT-OWNER",
         };
 
-        StandardError::new(code).interpolate_err(error.to_string())
+        StandardError::new(code)
+            .interpolate_err(error.to_string())
+            .with_source(error)
     }
 }
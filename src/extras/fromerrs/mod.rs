@@ -4,3 +4,4 @@ pub mod axum;
 pub mod git;
 pub mod stdio;
 pub mod reqwest;
+pub mod validator;
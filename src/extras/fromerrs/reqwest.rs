@@ -1,7 +1,8 @@
 #[cfg(feature = "reqwest")]
 use reqwest::Error as ReqwestError;
 
-use crate::{StandardError, Interpolate};
+#[cfg(feature = "reqwest")]
+use crate::{StandardError, Interpolate, Source};
 
 #[cfg(feature = "reqwest")]
 impl From<ReqwestError> for StandardError {
@@ -25,5 +26,6 @@ impl From<ReqwestError> for StandardError {
             StandardError::new("ER-REQWEST-UNKNOWN")
                 .interpolate_err(format!("An unknown Reqwest error occurred: {}", error))
         }
+        .with_source(error)
     }
 }
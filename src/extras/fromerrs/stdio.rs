@@ -1,5 +1,5 @@
 use std::io;
-use crate::{StandardError, Interpolate};
+use crate::{StandardError, Interpolate, Source};
 
 impl From<std::io::Error> for StandardError {
     fn from(error: io::Error) -> Self {
@@ -21,6 +21,8 @@ impl From<std::io::Error> for StandardError {
             _ => "ER-IO-UNKNOWN",
         };
 
-        StandardError::new(code).interpolate_err(error.to_string())
+        StandardError::new(code)
+            .interpolate_err(error.to_string())
+            .with_source(error)
     }
 }
@@ -0,0 +1,141 @@
+#[cfg(feature = "validator")]
+use validator::{ValidationError, ValidationErrors, ValidationErrorsKind};
+#[cfg(feature = "validator")]
+use http::StatusCode;
+#[cfg(feature = "validator")]
+use crate::{error_messages, Interpolate, Status, StandardError, StandardErrors, StatusRule};
+
+/// `must_match` -> `ER-VALIDATION-MUST-MATCH`
+#[cfg(feature = "validator")]
+fn validation_code(code: &str) -> String {
+    let code: String = code
+        .chars()
+        .map(|c| match c.is_ascii_alphanumeric() {
+            true => c.to_ascii_uppercase(),
+            false => '-',
+        })
+        .collect();
+    format!("ER-VALIDATION-{}", code)
+}
+
+#[cfg(feature = "validator")]
+fn from_validation_error(field: &str, error: &ValidationError) -> StandardError {
+    let mut values: std::collections::HashMap<String, String> = error
+        .params
+        .iter()
+        .map(|(k, v)| {
+            let v = match v {
+                serde_json::Value::String(s) => s.clone(),
+                v => v.to_string(),
+            };
+            (k.to_string(), v)
+        })
+        .collect();
+    values.insert("field".to_string(), field.to_string());
+    values.insert("code".to_string(), error.code.to_string());
+
+    let code = validation_code(&error.code);
+    let known = error_messages.contains_key(&code);
+    let mut err = StandardError::new(code).interpolate_values(values);
+    // a `status:` in the catalog wins over the 422 default
+    if err.metadata().status.is_none() {
+        err = err.code(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+    match (&error.message, field) {
+        // `#[validate(email(message = "..."))]` is what the developer wants shown
+        (Some(message), _) => err.occurrence_mut().message = Some(message.to_string()),
+        // custom validators without a catalog entry still get a readable message
+        _ if known => {}
+        (None, "") => err.occurrence_mut().fallback_message = Some(format!("Validation failed: {}", error.code)),
+        (None, field) => err.occurrence_mut().fallback_message = Some(format!("{} is invalid: {}", field, error.code)),
+    }
+    err
+}
+
+#[cfg(feature = "validator")]
+fn collect(errors: &ValidationErrors, pointer: &str, parent: &str, into: &mut StandardErrors) {
+    let mut fields: Vec<_> = errors.errors().iter().collect();
+    fields.sort_by_key(|(field, _)| *field);
+    for (field, kind) in fields {
+        // struct level (schema) errors are reported on the struct itself
+        let (field_pointer, field_name) = match *field {
+            "__all__" => (pointer.to_string(), parent),
+            field => (format!("{}/{}", pointer, field), field),
+        };
+        match kind {
+            ValidationErrorsKind::Field(field_errors) => {
+                for error in field_errors {
+                    let err = from_validation_error(field_name, error);
+                    match field_pointer.is_empty() {
+                        true => into.push(err),
+                        false => into.push_at(field_pointer.clone(), err),
+                    }
+                }
+            }
+            ValidationErrorsKind::Struct(nested) => collect(nested, &field_pointer, field_name, into),
+            ValidationErrorsKind::List(items) => {
+                for (index, nested) in items {
+                    collect(nested, &format!("{}/{}", field_pointer, index), field_name, into);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "validator")]
+impl From<ValidationErrors> for StandardErrors {
+    fn from(errors: ValidationErrors) -> Self {
+        log::debug!("validation errors: {}", &errors);
+        let mut into = StandardErrors::new().status_rule(StatusRule::Uniform(StatusCode::UNPROCESSABLE_ENTITY));
+        collect(&errors, "", "", &mut into);
+        into
+    }
+}
+
+#[cfg(all(test, feature = "validator"))]
+mod tests {
+    use super::*;
+    use std::borrow::Cow;
+
+    #[test]
+    fn test_validation_errors() {
+        let mut email = ValidationError::new("email");
+        email.add_param(Cow::from("value"), &"ashu");
+        let mut length = ValidationError::new("length");
+        length.add_param(Cow::from("min"), &3);
+        let custom = ValidationError::new("terrible_username");
+        let mut phone = ValidationError::new("regex");
+        phone.message = Some(Cow::from("Phone numbers look like +91 98765 43210"));
+
+        let mut item = ValidationErrors::new();
+        item.add("email", email);
+        let mut root = ValidationErrors::new();
+        root.add("name", length);
+        root.add("__all__", custom);
+        root.add("phone", phone);
+        root.errors_mut().insert(
+            "items",
+            ValidationErrorsKind::List([(3, Box::new(item))].into_iter().collect()),
+        );
+
+        let errors: StandardErrors = root.into();
+        assert_eq!(errors.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let items: Vec<_> = errors.iter().collect();
+        assert_eq!(items.len(), 4);
+
+        assert_eq!(items[0].error.err_code, "ER-VALIDATION-TERRIBLE-USERNAME");
+        assert_eq!(items[0].pointer, None);
+        assert_eq!(items[0].error.message(), "Validation failed: terrible_username");
+
+        assert_eq!(items[1].error.err_code, "ER-VALIDATION-EMAIL");
+        assert_eq!(items[1].pointer, Some("/items/3/email".to_string()));
+        assert_eq!(items[1].error.render("en_US"), "email must be a valid email address");
+
+        assert_eq!(items[2].error.err_code, "ER-VALIDATION-LENGTH");
+        assert_eq!(items[2].pointer, Some("/name".to_string()));
+        assert_eq!(items[2].error.values().get("min"), Some(&"3".to_string()));
+
+        assert_eq!(items[3].error.err_code, "ER-VALIDATION-REGEX");
+        assert_eq!(items[3].error.render("hi_IN"), "Phone numbers look like +91 98765 43210");
+    }
+}
@@ -0,0 +1,89 @@
+use std::sync::Arc;
+
+use async_graphql::{ErrorExtensionValues, ErrorExtensions, Value};
+
+use crate::{locale, StandardError};
+
+/// Put this in the request data (`request.data(GraphqlLocale("hi_IN".into()))`)
+/// to render error messages in the caller's locale
+#[derive(Debug, Clone)]
+pub struct GraphqlLocale(pub String);
+
+impl StandardError {
+    /// `{message, extensions: {code, status, values}}` with the message rendered in `locale`,
+    /// the error itself is kept as the source
+    pub fn graphql_error_in(&self, locale: &str) -> async_graphql::Error {
+        let mut extensions = ErrorExtensionValues::default();
+        extensions.set("code", self.err_code.to_string());
+        extensions.set("status", self.status_code.as_u16());
+        if let Ok(values) = Value::from_json(serde_json::json!(self.values)) {
+            extensions.set("values", values);
+        }
+        async_graphql::Error {
+            message: self.render(locale),
+            source: Some(Arc::new(self.clone())),
+            extensions: Some(extensions),
+        }
+    }
+
+    /// Renders in the `GraphqlLocale` from the request data, or the current locale without one
+    pub fn graphql_error(&self, ctx: &async_graphql::Context<'_>) -> async_graphql::Error {
+        match ctx.data_opt::<GraphqlLocale>() {
+            Some(GraphqlLocale(locale)) => self.graphql_error_in(locale),
+            None => self.extend(),
+        }
+    }
+}
+
+/// `async_graphql::Error` already converts from anything `Display`, which would only give
+/// `Error ER-0008 with status 404`, so go through `extend()` or `graphql_error(ctx)` instead
+impl ErrorExtensions for StandardError {
+    fn extend(&self) -> async_graphql::Error {
+        self.graphql_error_in(&locale::get_current_locale())
+    }
+}
+
+pub trait GraphqlResultExt<T> {
+    /// `Result<T, StandardError>` -> `async_graphql::Result<T>` localized from the request context
+    fn graphql(self, ctx: &async_graphql::Context<'_>) -> async_graphql::Result<T>;
+}
+
+impl<T> GraphqlResultExt<T> for Result<T, StandardError> {
+    fn graphql(self, ctx: &async_graphql::Context<'_>) -> async_graphql::Result<T> {
+        self.map_err(|err| err.graphql_error(ctx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Interpolate;
+    use async_graphql::{EmptyMutation, EmptySubscription, Object, Request, Schema};
+
+    struct Query;
+
+    #[Object]
+    impl Query {
+        async fn user(&self, ctx: &async_graphql::Context<'_>, id: String) -> async_graphql::Result<String> {
+            Err::<String, _>(StandardError::new("ER-0008").interpolate_values([("user_id".to_string(), id)].into()))
+                .graphql(ctx)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_graphql_error() {
+        let schema = Schema::new(Query, EmptyMutation, EmptySubscription);
+        let request = Request::new(r#"{ user(id: "42") }"#).data(GraphqlLocale("hi_IN".to_string()));
+        let response = serde_json::to_value(schema.execute(request).await).unwrap();
+        let error = &response["errors"][0];
+        assert_eq!(error["message"], "उपयोगकर्ता 42 नहीं मिला");
+        assert_eq!(error["path"], serde_json::json!(["user"]));
+        assert_eq!(error["extensions"]["code"], "ER-0008");
+        assert_eq!(error["extensions"]["status"], 404);
+        assert_eq!(error["extensions"]["values"]["user_id"], "42");
+
+        let err = StandardError::new("ER-0008").extend();
+        assert_eq!(err.message, "User [user_id] not found");
+        assert!(err.source.unwrap().downcast_ref::<StandardError>().is_some());
+    }
+}
@@ -0,0 +1,151 @@
+use http::StatusCode;
+use tonic::Code;
+use tonic_types::{ErrorDetails, StatusExt};
+
+use crate::extras::wire::rebuild;
+use crate::{locale, settings, Interpolate, Source, StandardError};
+
+/// 404 -> `NOT_FOUND`, 409 -> `ALREADY_EXISTS` and so on, following google's `google.rpc.Code` docs
+pub fn grpc_code(status: StatusCode) -> Code {
+    match status.as_u16() {
+        400 => Code::InvalidArgument,
+        401 => Code::Unauthenticated,
+        403 => Code::PermissionDenied,
+        404 => Code::NotFound,
+        409 => Code::AlreadyExists,
+        412 | 422 => Code::FailedPrecondition,
+        416 => Code::OutOfRange,
+        429 => Code::ResourceExhausted,
+        499 => Code::Cancelled,
+        501 => Code::Unimplemented,
+        503 => Code::Unavailable,
+        504 => Code::DeadlineExceeded,
+        _ if status.is_success() => Code::Ok,
+        _ if status.is_client_error() => Code::FailedPrecondition,
+        _ if status.is_server_error() => Code::Internal,
+        _ => Code::Unknown,
+    }
+}
+
+/// The reverse of `grpc_code`, used when the code has no status in the catalog
+pub fn http_status(code: Code) -> StatusCode {
+    match code {
+        Code::Ok => StatusCode::OK,
+        Code::InvalidArgument | Code::OutOfRange => StatusCode::BAD_REQUEST,
+        Code::FailedPrecondition => StatusCode::UNPROCESSABLE_ENTITY,
+        Code::Unauthenticated => StatusCode::UNAUTHORIZED,
+        Code::PermissionDenied => StatusCode::FORBIDDEN,
+        Code::NotFound => StatusCode::NOT_FOUND,
+        Code::AlreadyExists | Code::Aborted => StatusCode::CONFLICT,
+        Code::ResourceExhausted => StatusCode::TOO_MANY_REQUESTS,
+        Code::Cancelled => StatusCode::from_u16(499).unwrap_or(StatusCode::BAD_REQUEST),
+        Code::Unimplemented => StatusCode::NOT_IMPLEMENTED,
+        Code::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        Code::DeadlineExceeded => StatusCode::GATEWAY_TIMEOUT,
+        Code::Unknown | Code::Internal | Code::DataLoss => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// Catalog form of the `LocalizedMessage` locale of a status, the language its message is in
+pub fn grpc_locale(status: &tonic::Status) -> Option<String> {
+    status
+        .get_details_localized_message()
+        .map(|localized| locale::normalize_locale(&localized.locale))
+}
+
+impl StandardError {
+    /// The message is rendered in `locale`, the code and values go in `google.rpc.ErrorInfo`
+    /// and the message again in `google.rpc.LocalizedMessage`
+    pub fn grpc_status_in(&self, locale: &str) -> tonic::Status {
+        let message = self.render(locale);
+        let mut details = ErrorDetails::with_error_info(
+            self.err_code.to_string(),
+            settings.grpc_error_domain.clone(),
+            self.values.clone(),
+        );
+        // LocalizedMessage wants a BCP 47 tag, `hi-IN` rather than the catalog's `hi_IN`
+        details.set_localized_message(locale.replace('_', "-"), message.clone());
+        tonic::Status::with_error_details(grpc_code(self.status_code), message, details)
+    }
+
+    pub fn grpc_status(&self) -> tonic::Status {
+        self.grpc_status_in(&locale::get_current_locale())
+    }
+}
+
+impl From<StandardError> for tonic::Status {
+    fn from(err: StandardError) -> Self {
+        err.grpc_status()
+    }
+}
+
+/// Rebuilds the error from an `ErrorInfo` in our `grpc_error_domain`. Other statuses, including
+/// `ErrorInfo` reasons of other systems, become `ER-GRPC-STATUS` with the status as the source
+impl From<tonic::Status> for StandardError {
+    fn from(status: tonic::Status) -> Self {
+        let info = status
+            .get_details_error_info()
+            .filter(|info| info.domain == settings.grpc_error_domain);
+        let Some(info) = info else {
+            let mut err = StandardError::new("ER-GRPC-STATUS").interpolate_err(status.message().to_string());
+            err.status_code = http_status(status.code());
+            return err.with_source(status);
+        };
+        let message = status
+            .get_details_localized_message()
+            .map(|localized| localized.message)
+            .unwrap_or_else(|| status.message().to_string());
+        let mut err = rebuild(info.reason.clone(), None, info.metadata, message, None)
+            .unwrap_or_else(|_| StandardError::new(info.reason));
+        if err.metadata().status.is_none() {
+            err.status_code = http_status(status.code());
+        }
+        err
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grpc_code_mapping() {
+        assert_eq!(grpc_code(StatusCode::NOT_FOUND), Code::NotFound);
+        assert_eq!(grpc_code(StatusCode::CONFLICT), Code::AlreadyExists);
+        assert_eq!(grpc_code(StatusCode::IM_A_TEAPOT), Code::FailedPrecondition);
+        assert_eq!(grpc_code(StatusCode::BAD_GATEWAY), Code::Internal);
+        assert_eq!(http_status(grpc_code(StatusCode::UNAUTHORIZED)), StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn test_grpc_round_trip() {
+        let err = StandardError::new("ER-0008")
+            .interpolate_values([("user_id".to_string(), "42".to_string())].into());
+        let status = err.grpc_status_in("hi_IN");
+        assert_eq!(status.code(), Code::NotFound);
+        assert_eq!(status.message(), "उपयोगकर्ता 42 नहीं मिला");
+        let info = status.get_details_error_info().unwrap();
+        assert_eq!(info.reason, "ER-0008");
+        assert_eq!(info.metadata.get("user_id"), Some(&"42".to_string()));
+        assert_eq!(status.get_details_localized_message().unwrap().locale, "hi-IN");
+        assert_eq!(grpc_locale(&status), Some("hi_IN".to_string()));
+
+        let err = StandardError::from(status);
+        assert_eq!(err.err_code, "ER-0008");
+        assert_eq!(err.status_code, StatusCode::NOT_FOUND);
+        assert_eq!(err.render("en_US"), "User 42 not found");
+
+        let err = StandardError::from(tonic::Status::unavailable("connection refused"));
+        assert_eq!(err.err_code, "ER-GRPC-STATUS");
+        assert_eq!(err.status_code, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(err.render("en_US"), "gRPC error: connection refused");
+
+        // reasons are only codes of ours in our domain
+        let details = ErrorDetails::with_error_info("RATE_LIMIT_EXCEEDED", "googleapis.com", std::collections::HashMap::new());
+        let err = StandardError::from(tonic::Status::with_error_details(Code::ResourceExhausted, "quota exceeded", details));
+        assert_eq!(err.err_code, "ER-GRPC-STATUS");
+        assert_eq!(err.status_code, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(err.render("en_US"), "gRPC error: quota exceeded");
+        assert!(std::error::Error::source(&err).and_then(|s| s.downcast_ref::<tonic::Status>()).is_some());
+    }
+}
@@ -1,26 +1,116 @@
 use std::collections::HashMap;
+use std::str::FromStr;
 
+use crate::extras::metadata::ErrorMetadata;
 use crate::StandardError;
 
+/// How catalog templates are written, set with `syntax:` at the top of the yaml or on an entry
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TemplateSyntax {
+    /// `[placeholder]`
+    #[default]
+    Brackets,
+    /// ICU MessageFormat, `{count, plural, one {# item} other {# items}}`
+    #[cfg(feature = "icu")]
+    Icu,
+    /// Project Fluent, set for the codes loaded from `ERROR_FTL_DIR`
+    #[cfg(feature = "fluent")]
+    Fluent,
+}
+
+impl FromStr for TemplateSyntax {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "brackets" => Ok(TemplateSyntax::Brackets),
+            #[cfg(feature = "icu")]
+            "icu" => Ok(TemplateSyntax::Icu),
+            #[cfg(not(feature = "icu"))]
+            "icu" => Err("`syntax: icu` needs the `icu` feature of standard-error".to_string()),
+            _ => Err(format!("unknown template syntax: {}", s)),
+        }
+    }
+}
+
 pub trait Interpolate{
-    fn interpolate_values(&mut self, values: HashMap<String, String>) -> Self;
-    fn interpolate_err(&mut self, e: String) -> Self;
+    fn interpolate_values(self, values: HashMap<String, String>) -> Self;
+    fn interpolate_err(self, e: String) -> Self;
 }
 
 impl Interpolate for StandardError{
-    fn interpolate_values(&mut self, values: HashMap<String, String>) -> Self {
-        let mut new_message = self.message.clone(); // Clone the message to avoid mutating it directly
-        for (k, v) in values.into_iter() {
-            new_message = new_message.replace(&format!("[{}]", &k), &v); // Use new_message to accumulate changes
+    fn interpolate_values(mut self, values: HashMap<String, String>) -> Self {
+        // kept around, the message is rendered when it's needed
+        if self.values.is_empty() {
+            self.values = values;
+        } else {
+            self.values.extend(values);
+        }
+        self
+    }
+
+    fn interpolate_err(mut self, e: String) -> Self {
+        self.values.insert("err".to_string(), e);
+        self
+    }
+
+}
+
+/// Substitutes `[key]` placeholders in a single pass, unknown keys are left as they are
+pub(crate) fn render_template(template: &str, values: &HashMap<String, String>) -> String {
+    let mut message = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('[') {
+        message.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after.find(']').and_then(|end| values.get(&after[..end]).map(|v| (end, v))) {
+            Some((end, value)) => {
+                message.push_str(value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                message.push('[');
+                rest = after;
+            }
         }
-        self.message = new_message;
-        self.clone()
     }
+    message.push_str(rest);
+    message
+}
 
-    fn interpolate_err(&mut self, e: String) -> Self {
-        let mut values: HashMap<String, String> = HashMap::new();
-        values.insert("err".to_string(), e);
-        self.interpolate_values(values)
+/// `locale` is the one the template is written in, ICU plurals follow its rules
+#[cfg_attr(not(all(feature = "icu", feature = "fluent")), allow(unused_variables))]
+pub(crate) fn render_message(
+    metadata: &ErrorMetadata,
+    code: &str,
+    template: &str,
+    values: &HashMap<String, String>,
+    locale: &str,
+) -> String {
+    match metadata.syntax {
+        TemplateSyntax::Brackets => render_template(template, values),
+        #[cfg(feature = "icu")]
+        TemplateSyntax::Icu => match metadata.icu_templates.get(locale) {
+            Some(parsed) => crate::extras::message_format::render_parsed(parsed, values, locale),
+            None => crate::extras::message_format::render(template, values, locale),
+        },
+        #[cfg(feature = "fluent")]
+        TemplateSyntax::Fluent => crate::extras::fluent::render(code, template, values, locale),
     }
+}
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_template() {
+        let values: HashMap<String, String> = [("a", "1"), ("b", "[a]")]
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        assert_eq!(render_template("[a] and [b]", &values), "1 and [a]");
+        assert_eq!(render_template("[a] [missing] [[a]] [", &values), "1 [missing] [1] [");
+        assert_eq!(render_template("no placeholders", &values), "no placeholders");
+    }
 }
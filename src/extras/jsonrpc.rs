@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use http::StatusCode;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::extras::wire::rebuild;
+use crate::{locale, Interpolate, StandardError};
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+/// start of the `-32000..=-32099` range the spec leaves to the implementation
+pub const SERVER_ERROR: i64 = -32000;
+
+/// JSON-RPC 2.0 `error` object, `data` carries `{code, status, values}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonRpcError {
+    pub code: i64,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+/// How errors get their integer code: the catalog's `jsonrpc_code`, then `codes`, then `statuses`,
+/// then 400/422 -> invalid params, 405/501 -> method not found, 5xx -> internal error, else `-32000`
+#[derive(Debug, Clone, Default)]
+pub struct JsonRpcMapping {
+    pub codes: HashMap<String, i64>,
+    pub statuses: HashMap<StatusCode, i64>,
+}
+
+impl JsonRpcMapping {
+    pub fn code(mut self, err_code: &str, jsonrpc_code: i64) -> Self {
+        self.codes.insert(err_code.to_string(), jsonrpc_code);
+        self
+    }
+
+    pub fn status(mut self, status: StatusCode, jsonrpc_code: i64) -> Self {
+        self.statuses.insert(status, jsonrpc_code);
+        self
+    }
+
+    pub fn jsonrpc_code(&self, err: &StandardError) -> i64 {
+        if let Some(code) = err.metadata.jsonrpc_code {
+            return code;
+        }
+        if let Some(code) = self.codes.get(err.err_code.as_ref()) {
+            return *code;
+        }
+        if let Some(code) = self.statuses.get(&err.status_code) {
+            return *code;
+        }
+        match err.status_code.as_u16() {
+            400 | 422 => INVALID_PARAMS,
+            405 | 501 => METHOD_NOT_FOUND,
+            500..=599 => INTERNAL_ERROR,
+            _ => SERVER_ERROR,
+        }
+    }
+}
+
+/// Status for errors that come back without `data`
+fn status_for(jsonrpc_code: i64) -> StatusCode {
+    match jsonrpc_code {
+        PARSE_ERROR | INVALID_REQUEST | INVALID_PARAMS => StatusCode::BAD_REQUEST,
+        METHOD_NOT_FOUND => StatusCode::NOT_FOUND,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+lazy_static! {
+    static ref registered_mapping: RwLock<Arc<JsonRpcMapping>> = RwLock::new(Arc::new(JsonRpcMapping::default()));
+}
+
+pub fn set_jsonrpc_mapping(mapping: JsonRpcMapping) {
+    *registered_mapping.write().expect("jsonrpc mapping lock poisoned") = Arc::new(mapping);
+}
+
+pub fn jsonrpc_mapping() -> Arc<JsonRpcMapping> {
+    registered_mapping.read().expect("jsonrpc mapping lock poisoned").clone()
+}
+
+impl StandardError {
+    pub fn jsonrpc_error_in(&self, locale: &str) -> JsonRpcError {
+        JsonRpcError {
+            code: jsonrpc_mapping().jsonrpc_code(self),
+            message: self.render(locale),
+            data: Some(json!({
+                "code": self.err_code,
+                "status": self.status_code.as_u16(),
+                "values": self.values,
+            })),
+        }
+    }
+
+    pub fn jsonrpc_error(&self) -> JsonRpcError {
+        self.jsonrpc_error_in(&locale::get_current_locale())
+    }
+
+    /// Reads the `error` member of a JSON-RPC response, `None` if the response has no error
+    pub fn from_jsonrpc_response(body: &[u8]) -> Option<StandardError> {
+        let response: Value = serde_json::from_slice(body).ok()?;
+        let error: JsonRpcError = serde_json::from_value(response.get("error")?.clone()).ok()?;
+        Some(error.into())
+    }
+}
+
+impl From<StandardError> for JsonRpcError {
+    fn from(err: StandardError) -> Self {
+        err.jsonrpc_error()
+    }
+}
+
+/// Rebuilds the error from `data.code`, errors from other servers become `ER-JSONRPC-REMOTE`
+impl From<JsonRpcError> for StandardError {
+    fn from(error: JsonRpcError) -> Self {
+        let data = error.data.unwrap_or(Value::Null);
+        let status = data
+            .get("status")
+            .and_then(|v| v.as_u64())
+            .and_then(|v| u16::try_from(v).ok());
+        match data.get("code").and_then(|v| v.as_str()) {
+            Some(code) => {
+                let values: HashMap<String, String> = data
+                    .get("values")
+                    .and_then(|v| serde_json::from_value(v.clone()).ok())
+                    .unwrap_or_default();
+                rebuild(code.to_string(), status, values, error.message, None)
+                    .unwrap_or_else(|_| StandardError::new(code.to_string()))
+            }
+            None => {
+                let mut err = StandardError::new("ER-JSONRPC-REMOTE").interpolate_values(
+                    [
+                        ("code".to_string(), error.code.to_string()),
+                        ("err".to_string(), error.message),
+                    ]
+                    .into(),
+                );
+                err.status_code = status
+                    .and_then(|status| StatusCode::from_u16(status).ok())
+                    .unwrap_or_else(|| status_for(error.code));
+                err
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Status;
+
+    #[test]
+    fn test_jsonrpc_codes() {
+        let mapping = JsonRpcMapping::default()
+            .code("ER-0004", -32010)
+            .status(StatusCode::CONFLICT, -32009);
+        // from the catalog
+        assert_eq!(mapping.jsonrpc_code(&StandardError::new("ER-0008")), -32004);
+        assert_eq!(mapping.jsonrpc_code(&StandardError::new("ER-0004")), -32010);
+        let err = StandardError::new("ER-0005");
+        assert_eq!(mapping.jsonrpc_code(&err), INTERNAL_ERROR);
+        assert_eq!(mapping.jsonrpc_code(&err.clone().code(StatusCode::CONFLICT)), -32009);
+        assert_eq!(mapping.jsonrpc_code(&err.code(StatusCode::UNPROCESSABLE_ENTITY)), INVALID_PARAMS);
+    }
+
+    #[test]
+    fn test_jsonrpc_round_trip() {
+        let err = StandardError::new("ER-0008")
+            .interpolate_values([("user_id".to_string(), "42".to_string())].into());
+        let error = err.jsonrpc_error_in("en_US");
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            json!({"code": -32004, "message": "User 42 not found", "data": {"code": "ER-0008", "status": 404, "values": {"user_id": "42"}}})
+        );
+
+        let body = json!({"jsonrpc": "2.0", "error": error, "id": 1}).to_string();
+        let err = StandardError::from_jsonrpc_response(body.as_bytes()).unwrap();
+        assert_eq!(err.err_code, "ER-0008");
+        assert_eq!(err.status_code, StatusCode::NOT_FOUND);
+        assert_eq!(err.render("hi_IN"), "उपयोगकर्ता 42 नहीं मिला");
+
+        let body = br#"{"jsonrpc": "2.0", "error": {"code": -32601, "message": "Method not found"}, "id": 1}"#;
+        let err = StandardError::from_jsonrpc_response(body).unwrap();
+        assert_eq!(err.err_code, "ER-JSONRPC-REMOTE");
+        assert_eq!(err.status_code, StatusCode::NOT_FOUND);
+        assert_eq!(err.render("en_US"), "JSON-RPC error -32601: Method not found");
+
+        assert!(StandardError::from_jsonrpc_response(br#"{"jsonrpc": "2.0", "result": 7, "id": 1}"#).is_none());
+    }
+}
@@ -0,0 +1,223 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use http::header::{ACCEPT_LANGUAGE, CONTENT_LANGUAGE, COOKIE, VARY};
+use http::request::Parts;
+use http::{HeaderValue, Request, Response};
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::locale::{match_locale, negotiate_locale, with_locale};
+use crate::settings;
+
+type Resolver = Arc<dyn Fn(&Parts) -> Option<String> + Send + Sync>;
+
+/// `hi%2DIN` -> `hi-IN`, invalid escapes are kept as they are
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = match bytes[i] {
+            b'%' => value.get(i + 1..i + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(match bytes[i] {
+                    b'+' => b' ',
+                    byte => byte,
+                });
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Picks each request's locale and runs the handler inside `with_locale`. The first of these that
+/// names a catalog locale wins: the resolver, the query parameter, the cookie, `Accept-Language`,
+/// then `DEFAULT_LOCALE`. Error responses get a `Content-Language` header, and `Vary` on
+/// `Accept-Language` (and `Cookie` when a cookie is read)
+///
+/// ```ignore
+/// Router::new()
+///     .route("/users/:id", get(get_user))
+///     .layer(axum::middleware::from_fn(standard_error::finalize_errors))
+///     .layer(LocaleLayer::new().query_param("lang").cookie("locale"))
+/// ```
+#[derive(Clone, Default)]
+pub struct LocaleLayer {
+    query_param: Option<String>,
+    cookie: Option<String>,
+    resolver: Option<Resolver>,
+}
+
+impl LocaleLayer {
+    pub fn new() -> Self {
+        LocaleLayer::default()
+    }
+
+    pub fn query_param(mut self, name: &str) -> Self {
+        self.query_param = Some(name.to_string());
+        self
+    }
+
+    pub fn cookie(mut self, name: &str) -> Self {
+        self.cookie = Some(name.to_string());
+        self
+    }
+
+    /// e.g. the user's preferred locale from a JWT claim an auth layer put in the extensions
+    pub fn resolver<F>(mut self, resolver: F) -> Self
+    where
+        F: Fn(&Parts) -> Option<String> + Send + Sync + 'static,
+    {
+        self.resolver = Some(Arc::new(resolver));
+        self
+    }
+
+    fn resolve(&self, parts: &Parts) -> String {
+        let from_query = || {
+            let name = self.query_param.as_deref()?;
+            parts.uri.query()?.split('&').find_map(|pair| {
+                let (key, value) = pair.split_once('=')?;
+                (percent_decode(key) == name).then(|| percent_decode(value))
+            })
+        };
+        let from_cookie = || {
+            let name = self.cookie.as_deref()?;
+            parts.headers.get_all(COOKIE).iter().find_map(|header| {
+                header.to_str().ok()?.split(';').find_map(|pair| {
+                    let (key, value) = pair.trim().split_once('=')?;
+                    (key == name).then(|| percent_decode(value))
+                })
+            })
+        };
+        self.resolver
+            .as_ref()
+            .and_then(|resolver| resolver(parts))
+            .and_then(|locale| match_locale(&locale))
+            .or_else(|| from_query().and_then(|locale| match_locale(&locale)))
+            .or_else(|| from_cookie().and_then(|locale| match_locale(&locale)))
+            .or_else(|| {
+                let accept_language = parts.headers.get(ACCEPT_LANGUAGE)?.to_str().ok()?;
+                negotiate_locale(accept_language)
+            })
+            .unwrap_or_else(|| settings.default_locale.clone())
+    }
+}
+
+impl<S> Layer<S> for LocaleLayer {
+    type Service = LocaleService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        LocaleService {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct LocaleService<S> {
+    inner: S,
+    layer: LocaleLayer,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for LocaleService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let (parts, body) = req.into_parts();
+        let locale = self.layer.resolve(&parts);
+        let uses_cookie = self.layer.cookie.is_some();
+        let future = with_locale(locale.clone(), self.inner.call(Request::from_parts(parts, body)));
+        Box::pin(async move {
+            let mut response = future.await?;
+            let status = response.status();
+            if status.is_client_error() || status.is_server_error() {
+                let headers = response.headers_mut();
+                if let Ok(language) = HeaderValue::from_str(&locale.replace('_', "-")) {
+                    headers.insert(CONTENT_LANGUAGE, language);
+                }
+                // the body depends on these request headers, shared caches have to know
+                headers.append(VARY, HeaderValue::from_static("accept-language"));
+                if uses_cookie {
+                    headers.append(VARY, HeaderValue::from_static("cookie"));
+                }
+            }
+            Ok(response)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StandardError;
+    use axum::{body::Body, routing::get, Router};
+    use tower::ServiceExt;
+
+    async fn get_error(layer: LocaleLayer, uri: &str, headers: &[(&str, &str)]) -> (Option<String>, Vec<String>, String) {
+        let app = Router::new()
+            .route("/", get(|| async { Err::<(), _>(StandardError::new("ER-0004")) }))
+            .layer(layer);
+        let mut req = Request::builder().uri(uri);
+        for (name, value) in headers {
+            req = req.header(*name, *value);
+        }
+        let response = app.oneshot(req.body(Body::empty()).unwrap()).await.unwrap();
+        let language = response
+            .headers()
+            .get(CONTENT_LANGUAGE)
+            .map(|v| v.to_str().unwrap().to_string());
+        let vary = response
+            .headers()
+            .get_all(VARY)
+            .iter()
+            .map(|v| v.to_str().unwrap().to_string())
+            .collect();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        (language, vary, body["detail"].as_str().unwrap().to_string())
+    }
+
+    #[tokio::test]
+    async fn test_locale_layer() {
+        let (language, vary, detail) = get_error(LocaleLayer::new(), "/", &[("accept-language", "fr;q=1, hi-IN;q=0.9, en;q=0.5")]).await;
+        assert_eq!(language.as_deref(), Some("hi-IN"));
+        assert_eq!(vary, ["accept-language"]);
+        assert_eq!(detail, "एक पूर्णांक होना चाहिए");
+
+        let layer = LocaleLayer::new().query_param("lang").cookie("locale");
+        let (language, vary, _) = get_error(layer.clone(), "/?lang=en_US", &[("accept-language", "hi-IN"), ("cookie", "locale=hi_IN")]).await;
+        assert_eq!(language.as_deref(), Some("en-US"));
+        assert_eq!(vary, ["accept-language", "cookie"]);
+        let (language, _, _) = get_error(layer.clone(), "/?lang=hi%2DIN", &[("accept-language", "en-US")]).await;
+        assert_eq!(language.as_deref(), Some("hi-IN"));
+        let (language, _, _) = get_error(layer, "/?lang=fr", &[("cookie", "theme=dark; locale=hi_IN")]).await;
+        assert_eq!(language.as_deref(), Some("hi-IN"));
+
+        let layer = LocaleLayer::new().resolver(|parts| parts.headers.get("x-user-locale")?.to_str().ok().map(|s| s.to_string()));
+        let (language, _, detail) = get_error(layer, "/", &[("accept-language", "en-US"), ("x-user-locale", "hi")]).await;
+        assert_eq!(language.as_deref(), Some("hi-IN"));
+        assert_eq!(detail, "एक पूर्णांक होना चाहिए");
+    }
+}
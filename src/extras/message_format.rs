@@ -0,0 +1,366 @@
+//! The part of ICU MessageFormat error messages need: `{name}`, `{name, plural, ...}`,
+//! `{name, selectordinal, ...}` and `{name, select, ...}`, with `#` and `'` quoting.
+//! Plural categories follow the CLDR rules of the locale the template is written in
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use intl_pluralrules::{PluralCategory, PluralRuleType, PluralRules};
+use unic_langid::LanguageIdentifier;
+
+thread_local! {
+    /// cardinal and ordinal rules of each catalog locale, `PluralRules::create` isn't cheap
+    static PLURAL_RULES: RefCell<HashMap<String, [Option<PluralRules>; 2]>> = RefCell::new(HashMap::new());
+}
+
+/// A template parsed when the catalog is loaded, kept in `ErrorMetadata` per locale
+#[derive(Debug, PartialEq)]
+pub(crate) struct Template {
+    parts: Vec<Part>,
+    /// of the source, a good guess at the rendered length
+    len: usize,
+}
+
+#[derive(Debug, PartialEq)]
+enum Part {
+    Text(String),
+    Arg(String),
+    /// `#` inside a plural case
+    Number,
+    Plural {
+        name: String,
+        offset: f64,
+        ordinal: bool,
+        cases: Vec<(String, Vec<Part>)>,
+    },
+    Select {
+        name: String,
+        cases: Vec<(String, Vec<Part>)>,
+    },
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(c) => Err(format!("expected `{}` at {}, found `{}`", expected, self.pos, c)),
+            None => Err(format!("expected `{}` at the end", expected)),
+        }
+    }
+
+    fn word(&mut self) -> String {
+        self.skip_whitespace();
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| !c.is_whitespace() && !matches!(c, '{' | '}' | ','))
+        {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    fn message(&mut self, in_plural: bool, nested: bool) -> Result<Vec<Part>, String> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        while let Some(c) = self.peek() {
+            match c {
+                '\'' => {
+                    self.pos += 1;
+                    match self.peek() {
+                        Some('\'') => {
+                            text.push('\'');
+                            self.pos += 1;
+                        }
+                        Some('{' | '}' | '#') => {
+                            // quoted literal up to the next lone apostrophe
+                            while let Some(q) = self.peek() {
+                                self.pos += 1;
+                                if q != '\'' {
+                                    text.push(q);
+                                } else if self.peek() == Some('\'') {
+                                    text.push('\'');
+                                    self.pos += 1;
+                                } else {
+                                    break;
+                                }
+                            }
+                        }
+                        _ => text.push('\''),
+                    }
+                }
+                '{' => {
+                    self.pos += 1;
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(self.argument(in_plural)?);
+                }
+                '}' if nested => break,
+                '}' => return Err(format!("unmatched `}}` at {}", self.pos)),
+                '#' if in_plural => {
+                    self.pos += 1;
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(Part::Number);
+                }
+                c => {
+                    text.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+        if nested && self.peek().is_none() {
+            return Err("unclosed `{`".to_string());
+        }
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+        Ok(parts)
+    }
+
+    /// Everything after an argument's `{`, up to and including its `}`
+    fn argument(&mut self, in_plural: bool) -> Result<Part, String> {
+        let name = self.word();
+        if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return Err(format!("invalid argument name `{}` at {}", name, self.pos));
+        }
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.pos += 1;
+            return Ok(Part::Arg(name));
+        }
+        self.expect(',')?;
+        let kind = self.word();
+        let part = match kind.as_str() {
+            "plural" | "selectordinal" => {
+                self.expect(',')?;
+                self.skip_whitespace();
+                let mut offset = 0.0;
+                let rest: String = self.chars[self.pos..].iter().take(7).collect();
+                if rest == "offset:" {
+                    self.pos += 7;
+                    let value = self.word();
+                    offset = value.parse().map_err(|_| format!("invalid offset `{}`", value))?;
+                }
+                let cases = self.cases(true)?;
+                Part::Plural { name, offset, ordinal: kind == "selectordinal", cases }
+            }
+            "select" => {
+                self.expect(',')?;
+                let cases = self.cases(in_plural)?;
+                Part::Select { name, cases }
+            }
+            // number, date and friends are shown as they are
+            _ => {
+                let mut depth = 0;
+                while let Some(c) = self.peek() {
+                    match c {
+                        '{' => depth += 1,
+                        '}' if depth == 0 => break,
+                        '}' => depth -= 1,
+                        _ => {}
+                    }
+                    self.pos += 1;
+                }
+                Part::Arg(name)
+            }
+        };
+        self.expect('}')?;
+        Ok(part)
+    }
+
+    fn cases(&mut self, in_plural: bool) -> Result<Vec<(String, Vec<Part>)>, String> {
+        let mut cases = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.peek() == Some('}') || self.peek().is_none() {
+                break;
+            }
+            let selector = self.word();
+            self.expect('{')?;
+            let message = self.message(in_plural, true)?;
+            self.expect('}')?;
+            cases.push((selector, message));
+        }
+        if !cases.iter().any(|(selector, _)| selector == "other") {
+            return Err("missing the `other` case".to_string());
+        }
+        Ok(cases)
+    }
+}
+
+/// Done when the catalog is loaded, so a template that doesn't parse fails the load
+pub(crate) fn parse(template: &str) -> Result<Template, String> {
+    let parts = Parser { chars: template.chars().collect(), pos: 0 }.message(false, false)?;
+    Ok(Template { parts, len: template.len() })
+}
+
+fn plural_rules(locale: &str, rule_type: PluralRuleType) -> Option<PluralRules> {
+    let langid: LanguageIdentifier = locale.replace('_', "-").parse().ok()?;
+    PluralRules::create(langid.clone(), rule_type)
+        .or_else(|_| PluralRules::create(LanguageIdentifier::from_parts(langid.language, None, None, &[]), rule_type))
+        .ok()
+}
+
+/// `locale` is the catalog locale the template came from, so the cache stays as small as the catalog
+fn plural_category(locale: &str, ordinal: bool, number: f64) -> Option<&'static str> {
+    let category = PLURAL_RULES.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        if !cache.contains_key(locale) {
+            let rules = [
+                plural_rules(locale, PluralRuleType::CARDINAL),
+                plural_rules(locale, PluralRuleType::ORDINAL),
+            ];
+            cache.insert(locale.to_string(), rules);
+        }
+        cache[locale][ordinal as usize].as_ref()?.select(number).ok()
+    })?;
+    Some(match category {
+        PluralCategory::ZERO => "zero",
+        PluralCategory::ONE => "one",
+        PluralCategory::TWO => "two",
+        PluralCategory::FEW => "few",
+        PluralCategory::MANY => "many",
+        PluralCategory::OTHER => "other",
+    })
+}
+
+fn format_number(n: f64) -> String {
+    if n.fract() == 0.0 && n.abs() < 1e15 {
+        format!("{}", n as i64)
+    } else {
+        n.to_string()
+    }
+}
+
+fn pick<'a>(cases: &'a [(String, Vec<Part>)], selector: &str) -> &'a [Part] {
+    cases
+        .iter()
+        .find(|(s, _)| s == selector)
+        .or_else(|| cases.iter().find(|(s, _)| s == "other"))
+        .map(|(_, parts)| parts.as_slice())
+        .unwrap_or_default()
+}
+
+fn render_parts(parts: &[Part], values: &HashMap<String, String>, locale: &str, number: Option<&str>, out: &mut String) {
+    for part in parts {
+        match part {
+            Part::Text(text) => out.push_str(text),
+            Part::Arg(name) => match values.get(name) {
+                Some(value) => out.push_str(value),
+                None => {
+                    out.push('{');
+                    out.push_str(name);
+                    out.push('}');
+                }
+            },
+            Part::Number => out.push_str(number.unwrap_or("#")),
+            Part::Plural { name, offset, ordinal, cases } => {
+                let value = values.get(name);
+                match value.and_then(|v| v.trim().parse::<f64>().ok()) {
+                    Some(n) => {
+                        let exact = cases.iter().find(|(selector, _)| {
+                            selector.strip_prefix('=').and_then(|s| s.parse::<f64>().ok()) == Some(n)
+                        });
+                        let shown = match *offset == 0.0 {
+                            true => value.map(|v| v.trim().to_string()).unwrap_or_default(),
+                            false => format_number(n - offset),
+                        };
+                        let case = match exact {
+                            Some((_, parts)) => parts.as_slice(),
+                            None => pick(cases, plural_category(locale, *ordinal, n - offset).unwrap_or("other")),
+                        };
+                        render_parts(case, values, locale, Some(&shown), out);
+                    }
+                    None => render_parts(pick(cases, "other"), values, locale, value.map(|v| v.as_str()), out),
+                }
+            }
+            Part::Select { name, cases } => {
+                let selector = values.get(name).map(|v| v.as_str()).unwrap_or("other");
+                render_parts(pick(cases, selector), values, locale, number, out);
+            }
+        }
+    }
+}
+
+/// Renders with the plural rules of `locale`
+pub(crate) fn render_parsed(template: &Template, values: &HashMap<String, String>, locale: &str) -> String {
+    let mut out = String::with_capacity(template.len);
+    render_parts(&template.parts, values, locale, None, &mut out);
+    out
+}
+
+/// For templates the catalog didn't parse ahead, one that doesn't parse is returned as it is
+pub(crate) fn render(template: &str, values: &HashMap<String, String>, locale: &str) -> String {
+    match parse(template) {
+        Ok(parsed) => render_parsed(&parsed, values, locale),
+        Err(e) => {
+            log::warn!("invalid message format template {:?}: {}", template, e);
+            template.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_plural() {
+        let template = "{count, plural, =0 {No items} one {# item} other {# items}} in [cart]";
+        assert_eq!(render(template, &values(&[("count", "0")]), "en_US"), "No items in [cart]");
+        assert_eq!(render(template, &values(&[("count", "1")]), "en_US"), "1 item in [cart]");
+        assert_eq!(render(template, &values(&[("count", "3")]), "en_US"), "3 items in [cart]");
+        // hindi uses `one` for 0 and 1
+        let template = "{count, plural, one {# वस्तु} other {# वस्तुएँ}}";
+        assert_eq!(render(template, &values(&[("count", "0")]), "hi_IN"), "0 वस्तु");
+        assert_eq!(render(template, &values(&[("count", "2")]), "hi_IN"), "2 वस्तुएँ");
+        let template = "{n, plural, offset:1 =1 {just you} one {you and # other} other {you and # others}}";
+        assert_eq!(render(template, &values(&[("n", "2")]), "en_US"), "you and 1 other");
+        assert_eq!(render(template, &values(&[("n", "4")]), "en_US"), "you and 3 others");
+        let template = "{n, selectordinal, one {#st} two {#nd} few {#rd} other {#th}}";
+        assert_eq!(render(template, &values(&[("n", "22")]), "en_US"), "22nd");
+    }
+
+    #[test]
+    fn test_select() {
+        let template = "{gender, select, female {{name} ने अपना} male {{name} ने अपना} other {{name} ने}} '{'quoted'}' it''s";
+        assert_eq!(
+            render(template, &values(&[("gender", "female"), ("name", "Asha")]), "hi_IN"),
+            "Asha ने अपना {quoted} it's"
+        );
+        assert_eq!(render(template, &values(&[]), "hi_IN"), "{name} ने {quoted} it's");
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(parse("{count, plural, one {# item}}").is_err());
+        assert!(parse("{count, plural, other {# items}").is_err());
+        assert!(parse("unmatched }").is_err());
+        assert!(parse("{count, plural, other {# items}} and {name}").is_ok());
+    }
+}
@@ -0,0 +1,102 @@
+use std::{collections::HashMap, fmt, str::FromStr, sync::Arc};
+
+use http::StatusCode;
+use serde::{Deserialize, Serialize};
+
+use crate::extras::interpolate::TemplateSyntax;
+use crate::StandardError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Debug,
+    Info,
+    Warning,
+    Error,
+    Critical,
+}
+
+impl FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "debug" => Ok(Severity::Debug),
+            "info" => Ok(Severity::Info),
+            "warning" | "warn" => Ok(Severity::Warning),
+            "error" => Ok(Severity::Error),
+            "critical" => Ok(Severity::Critical),
+            _ => Err(format!("unknown severity: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Severity::Debug => "debug",
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+            Severity::Critical => "critical",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Per-code defaults read from the `errors.yaml` catalog, shared by every `StandardError` of that code
+/// until one of the `Metadata` overrides copies it
+#[derive(Debug, Clone, Default)]
+pub struct ErrorMetadata {
+    /// catalog default, the effective one is `StandardError::status_code`
+    pub status: Option<StatusCode>,
+    pub severity: Option<Severity>,
+    pub retryable: Option<bool>,
+    pub docs_url: Option<String>,
+    /// `code` of the JSON-RPC error object, see `JsonRpcMapping`
+    pub jsonrpc_code: Option<i64>,
+    /// short localized summaries from the `title_<locale>` keys
+    pub titles: HashMap<String, String>,
+    /// localized next steps from the `hint_<locale>` keys or `.hint` fluent attributes
+    pub hints: HashMap<String, String>,
+    pub meta: HashMap<String, serde_json::Value>,
+    pub syntax: TemplateSyntax,
+    /// `detail_<locale>` templates of `syntax: icu` entries, parsed once when the catalog is loaded
+    #[cfg(feature = "icu")]
+    pub(crate) icu_templates: HashMap<String, Arc<crate::extras::message_format::Template>>,
+}
+
+pub trait Metadata{
+    fn severity(self, severity: Severity) -> Self;
+    fn retryable(self, retryable: bool) -> Self;
+    fn docs_url(self, url: &str) -> Self;
+    fn meta(self, key: &str, value: serde_json::Value) -> Self;
+    fn instance(self, uri: &str) -> Self;
+}
+
+impl Metadata for StandardError{
+    fn severity(mut self, severity: Severity) -> Self {
+        Arc::make_mut(&mut self.metadata).severity = Some(severity);
+        self
+    }
+
+    fn retryable(mut self, retryable: bool) -> Self {
+        Arc::make_mut(&mut self.metadata).retryable = Some(retryable);
+        self
+    }
+
+    fn docs_url(mut self, url: &str) -> Self {
+        Arc::make_mut(&mut self.metadata).docs_url = Some(url.to_string());
+        self
+    }
+
+    fn meta(mut self, key: &str, value: serde_json::Value) -> Self {
+        Arc::make_mut(&mut self.metadata).meta.insert(key.to_string(), value);
+        self
+    }
+
+    fn instance(mut self, uri: &str) -> Self {
+        self.occurrence_mut().instance = Some(uri.to_string());
+        self
+    }
+}
@@ -1,4 +1,27 @@
+#[cfg(feature = "actix")]
+pub mod actix;
+pub mod aggregate;
+pub mod context;
+#[cfg(feature = "fluent")]
+pub mod fluent;
+pub mod formatter;
+#[cfg(feature = "async-graphql")]
+pub mod graphql;
+#[cfg(feature = "tonic")]
+pub mod grpc;
 pub mod interpolate;
+pub mod jsonrpc;
+#[cfg(feature = "axum")]
+pub mod locale_layer;
+#[cfg(feature = "icu")]
+pub mod message_format;
+pub mod metadata;
+pub mod negotiate;
+pub mod problem;
+pub mod remote;
+#[cfg(feature = "axum")]
 pub mod response;
+pub mod source;
 pub mod status;
+pub mod wire;
 pub mod fromerrs;
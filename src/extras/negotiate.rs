@@ -0,0 +1,221 @@
+use std::fmt::Write;
+
+use crate::{StandardError, StandardErrors};
+
+/// The error representations a client can ask for through `Accept`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Representation {
+    Json,
+    Text,
+    Html,
+    Xml,
+}
+
+impl Representation {
+    /// Picks the representation with the highest q-value, earlier entries win ties.
+    /// Anything we don't know about (and `*/*`) means json
+    pub fn from_accept(accept: &str) -> Representation {
+        let mut best: Option<(Representation, f32)> = None;
+        for entry in accept.split(',') {
+            let mut parts = entry.split(';');
+            let media_type = parts.next().unwrap_or_default().trim().to_ascii_lowercase();
+            let q = parts
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            let representation = match media_type.as_str() {
+                "application/json" | "application/problem+json" | "*/*" | "application/*" => Representation::Json,
+                "text/plain" => Representation::Text,
+                "text/html" | "application/xhtml+xml" | "text/*" => Representation::Html,
+                "application/xml" | "text/xml" | "application/problem+xml" => Representation::Xml,
+                _ => continue,
+            };
+            if q > 0.0 && best.is_none_or(|(_, best_q)| q > best_q) {
+                best = Some((representation, q));
+            }
+        }
+        best.map_or(Representation::Json, |(representation, _)| representation)
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Representation::Json => "application/json",
+            Representation::Text => "text/plain; charset=utf-8",
+            Representation::Html => "text/html; charset=utf-8",
+            Representation::Xml => "application/xml; charset=utf-8",
+        }
+    }
+}
+
+/// Escapes `& < > " '`, good for both html text and attributes
+pub fn escape_markup(s: &str) -> String {
+    escape(s, false)
+}
+
+/// `escape_markup` for xml, where C0 controls other than tab, newline and CR aren't allowed even
+/// as character references. They become U+FFFD
+pub fn escape_xml(s: &str) -> String {
+    escape(s, true)
+}
+
+fn escape(s: &str, xml: bool) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\t' | '\n' | '\r' => escaped.push(c),
+            '\u{0}'..='\u{1f}' | '\u{fffe}' | '\u{ffff}' if xml => escaped.push('\u{fffd}'),
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn html_page(locale: &str, title: &str, content: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"{}\">\n<head><meta charset=\"utf-8\"><title>{}</title></head>\n<body>\n{}</body>\n</html>\n",
+        escape_markup(&locale.replace('_', "-")),
+        escape_markup(title),
+        content
+    )
+}
+
+fn xml_error(err: &StandardError, locale: &str, out: &mut String) {
+    let _ = write!(
+        out,
+        "<error><code>{}</code><status>{}</status><title>{}</title><detail>{}</detail>",
+        escape_xml(&err.err_code),
+        err.status_code.as_u16(),
+        escape_xml(&err.title(locale)),
+        escape_xml(&err.render(locale)),
+    );
+    if !err.values.is_empty() {
+        let mut values: Vec<_> = err.values.iter().collect();
+        values.sort();
+        out.push_str("<values>");
+        for (name, value) in values {
+            let _ = write!(out, "<value name=\"{}\">{}</value>", escape_xml(name), escape_xml(value));
+        }
+        out.push_str("</values>");
+    }
+    out.push_str("</error>");
+}
+
+const XML_DECLARATION: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n";
+
+impl StandardError {
+    /// Body for the non-json representations, `None` for json which goes through the `ResponseFormatter`
+    pub fn render_as(&self, representation: Representation, locale: &str) -> Option<String> {
+        let message = self.render(locale);
+        match representation {
+            Representation::Json => None,
+            Representation::Text => Some(format!("{} {}: {}\n", self.status_code.as_u16(), self.err_code, message)),
+            Representation::Html => Some(html_page(
+                locale,
+                &self.title(locale),
+                &format!(
+                    "<h1>{}</h1>\n<p>{}</p>\n<p><code>{}</code></p>\n",
+                    escape_markup(&self.title(locale)),
+                    escape_markup(&message),
+                    escape_markup(&self.err_code)
+                ),
+            )),
+            Representation::Xml => {
+                let mut out = XML_DECLARATION.to_string();
+                xml_error(self, locale, &mut out);
+                out.push('\n');
+                Some(out)
+            }
+        }
+    }
+}
+
+impl StandardErrors {
+    pub fn render_as(&self, representation: Representation, locale: &str) -> Option<String> {
+        match representation {
+            Representation::Json => None,
+            Representation::Text => Some(
+                self.iter()
+                    .map(|item| match &item.pointer {
+                        Some(pointer) => format!("{} {}: {}\n", item.error.err_code, pointer, item.error.render(locale)),
+                        None => format!("{}: {}\n", item.error.err_code, item.error.render(locale)),
+                    })
+                    .collect(),
+            ),
+            Representation::Html => {
+                let title = self.status().canonical_reason().unwrap_or_default();
+                let items: String = self
+                    .iter()
+                    .map(|item| {
+                        format!(
+                            "<li><code>{}</code> {}</li>\n",
+                            escape_markup(item.pointer.as_deref().unwrap_or(&item.error.err_code)),
+                            escape_markup(&item.error.render(locale))
+                        )
+                    })
+                    .collect();
+                Some(html_page(locale, title, &format!("<h1>{}</h1>\n<ul>\n{}</ul>\n", escape_markup(title), items)))
+            }
+            Representation::Xml => {
+                let mut out = XML_DECLARATION.to_string();
+                let _ = write!(out, "<errors status=\"{}\">", self.status().as_u16());
+                for item in self.iter() {
+                    let _ = match &item.pointer {
+                        Some(pointer) => write!(out, "<item pointer=\"{}\">", escape_xml(pointer)),
+                        None => write!(out, "<item>"),
+                    };
+                    xml_error(&item.error, locale, &mut out);
+                    out.push_str("</item>");
+                }
+                out.push_str("</errors>\n");
+                Some(out)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Interpolate;
+
+    #[test]
+    fn test_from_accept() {
+        assert_eq!(Representation::from_accept("text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8"), Representation::Html);
+        assert_eq!(Representation::from_accept("text/plain;q=0.5, application/xml"), Representation::Xml);
+        assert_eq!(Representation::from_accept("application/json, text/plain"), Representation::Json);
+        assert_eq!(Representation::from_accept("text/html;q=0, text/plain;q=0.1"), Representation::Text);
+        assert_eq!(Representation::from_accept("image/png"), Representation::Json);
+        assert_eq!(Representation::from_accept(""), Representation::Json);
+    }
+
+    #[test]
+    fn test_escaping() {
+        let err = StandardError::new("ER-0008")
+            .interpolate_values([("user_id".to_string(), "<script>alert('x')</script>&".to_string())].into());
+
+        let html = err.render_as(Representation::Html, "en_US").unwrap();
+        assert!(html.contains("<p>User &lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt;&amp; not found</p>"));
+        assert!(html.contains("<html lang=\"en-US\">"));
+        assert!(!html.contains("<script>"));
+
+        let xml = err.render_as(Representation::Xml, "en_US").unwrap();
+        assert!(xml.contains("<detail>User &lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt;&amp; not found</detail>"));
+        assert!(xml.contains("<value name=\"user_id\">&lt;script&gt;"));
+
+        let text = err.render_as(Representation::Text, "en_US").unwrap();
+        assert_eq!(text, "404 ER-0008: User <script>alert('x')</script>& not found\n");
+
+        // C0 controls aren't allowed in xml 1.0, not even escaped
+        let control = StandardError::new("ER-0008")
+            .interpolate_values([("user_id".to_string(), "a\u{7}b\u{0}\tc\u{ffff}".to_string())].into());
+        let xml = control.render_as(Representation::Xml, "en_US").unwrap();
+        assert!(xml.contains("<detail>User a\u{fffd}b\u{fffd}\tc\u{fffd} not found</detail>"));
+        let html = control.render_as(Representation::Html, "en_US").unwrap();
+        assert!(html.contains("User a\u{7}b"));
+    }
+}
@@ -0,0 +1,108 @@
+use serde_json::{json, Value};
+
+use crate::{locale, settings, StandardError, StandardErrors};
+
+pub const PROBLEM_CONTENT_TYPE: &str = "application/problem+json";
+
+impl StandardError {
+    /// Localized `title_<locale>` from the catalog, or the status' reason phrase
+    pub fn title(&self, locale: &str) -> String {
+        locale::resolve(&self.metadata.titles, locale)
+            .map(|(_, title)| title.to_string())
+            .or_else(|| self.status_code.canonical_reason().map(|r| r.to_string()))
+            .unwrap_or_else(|| self.status_code.to_string())
+    }
+
+    /// Localized `hint_<locale>` from the catalog, what the caller can do about the error
+    pub fn hint(&self, locale: &str) -> Option<String> {
+        locale::resolve(&self.metadata.hints, locale).map(|(_, hint)| hint.to_string())
+    }
+
+    /// The per-code `docs_url`, or `<PROBLEM_TYPE_BASE_URI>/<code>`, or `about:blank`
+    pub fn problem_type(&self) -> String {
+        match (&self.metadata.docs_url, &settings.problem_type_base_uri) {
+            (Some(docs_url), _) => docs_url.clone(),
+            (None, Some(base)) => format!("{}/{}", base.trim_end_matches('/'), self.err_code),
+            (None, None) => "about:blank".to_string(),
+        }
+    }
+
+    /// RFC 9457 problem details, `code`, `hint`, `values` and `causes` are extension members
+    pub fn problem_details(&self) -> Value {
+        self.problem_details_in(&locale::get_current_locale())
+    }
+
+    pub fn problem_details_in(&self, locale: &str) -> Value {
+        let mut problem = json!({
+            "type": self.problem_type(),
+            "title": self.title(locale),
+            "status": self.status_code.as_u16(),
+            "detail": self.render(locale),
+            "code": self.err_code,
+        });
+        if let Some(hint) = self.hint(locale) {
+            problem["hint"] = json!(hint);
+        }
+        if let Some(instance) = self.instance_uri() {
+            problem["instance"] = json!(instance);
+        }
+        if !self.values.is_empty() {
+            problem["values"] = json!(self.values);
+        }
+        let causes = self.causes();
+        if !causes.is_empty() {
+            problem["causes"] = causes
+                .iter()
+                .map(|cause| json!({"code": cause.err_code, "detail": cause.render(locale)}))
+                .collect();
+        }
+        problem
+    }
+}
+
+impl StandardErrors {
+    pub fn problem_details(&self) -> Value {
+        self.problem_details_in(&locale::get_current_locale())
+    }
+
+    pub fn problem_details_in(&self, locale: &str) -> Value {
+        let status = self.status();
+        let mut problem = self.body(locale);
+        problem["type"] = json!("about:blank");
+        problem["title"] = json!(status.canonical_reason().unwrap_or_default());
+        problem["status"] = json!(status.as_u16());
+        problem
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Interpolate, Metadata};
+
+    #[test]
+    fn test_problem_details() {
+        crate::set_current_locale("hi_IN");
+        let problem = StandardError::new("ER-0008")
+            .interpolate_values([("user_id".to_string(), "42".to_string())].into())
+            .instance("/users/42")
+            .problem_details();
+        crate::set_current_locale("en_US");
+        assert_eq!(
+            problem,
+            json!({
+                "type": "https://docs.example.com/errors/ER-0008",
+                "title": "उपयोगकर्ता नहीं मिला",
+                "status": 404,
+                "detail": "उपयोगकर्ता 42 नहीं मिला",
+                "instance": "/users/42",
+                "code": "ER-0008",
+                "values": {"user_id": "42"},
+            })
+        );
+
+        let problem = StandardError::new("ER-0004").problem_details();
+        assert_eq!(problem["type"], "about:blank");
+        assert_eq!(problem["title"], "Internal Server Error");
+    }
+}
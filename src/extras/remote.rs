@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+
+use http::StatusCode;
+use serde_json::Value;
+
+use crate::extras::wire::{rebuild, StandardErrorWire};
+use crate::{Interpolate, StandardError};
+
+/// How errors coming back from another service are mapped to local ones
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RemotePolicy {
+    /// report remote 5xx responses as a local 502, the code and values are kept
+    pub bad_gateway_on_5xx: bool,
+}
+
+impl RemotePolicy {
+    pub fn bad_gateway() -> Self {
+        RemotePolicy { bad_gateway_on_5xx: true }
+    }
+
+    fn apply(&self, mut err: StandardError) -> StandardError {
+        if self.bad_gateway_on_5xx && err.status_code.is_server_error() {
+            err.status_code = StatusCode::BAD_GATEWAY;
+        }
+        err
+    }
+}
+
+impl StandardError {
+    /// Rebuilds the error a standard-error service answered with. Understands the serialized
+    /// `StandardError`, problem+json and the legacy `{"detail": ...}` bodies. When the body has no
+    /// code, `ER-REQWEST-REMOTE` is used with the remote detail as `[err]`
+    pub fn from_remote(status: StatusCode, body: &[u8], policy: RemotePolicy) -> StandardError {
+        let json: Value = serde_json::from_slice(body).unwrap_or(Value::Null);
+        if let Ok(wire) = serde_json::from_value::<StandardErrorWire>(json.clone()) {
+            if let Ok(mut err) = StandardError::try_from(wire) {
+                err.status_code = status;
+                return policy.apply(err);
+            }
+        }
+
+        let detail = json
+            .get("detail")
+            .or_else(|| json.get("title"))
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| match std::str::from_utf8(body) {
+                Ok(text) if !text.trim().is_empty() => text.trim().to_string(),
+                _ => status.to_string(),
+            });
+        let err = match json.get("code").and_then(|v| v.as_str()) {
+            Some(code) => {
+                let values: HashMap<String, String> = json
+                    .get("values")
+                    .and_then(|v| serde_json::from_value(v.clone()).ok())
+                    .unwrap_or_default();
+                rebuild(code.to_string(), Some(status.as_u16()), values, detail, None)
+                    .unwrap_or_else(|_| StandardError::new(code.to_string()))
+            }
+            None => {
+                let mut err = StandardError::new("ER-REQWEST-REMOTE").interpolate_err(detail);
+                err.status_code = status;
+                err
+            }
+        };
+        policy.apply(err)
+    }
+}
+
+#[cfg(feature = "reqwest")]
+pub trait ResponseExt: Sized {
+    /// Like `reqwest::Response::error_for_status`, but reads the standard error body
+    fn error_for_standard(
+        self,
+    ) -> impl std::future::Future<Output = Result<reqwest::Response, StandardError>> + Send {
+        self.error_for_standard_with(RemotePolicy::default())
+    }
+
+    fn error_for_standard_with(
+        self,
+        policy: RemotePolicy,
+    ) -> impl std::future::Future<Output = Result<reqwest::Response, StandardError>> + Send;
+}
+
+#[cfg(feature = "reqwest")]
+impl ResponseExt for reqwest::Response {
+    async fn error_for_standard_with(
+        self,
+        policy: RemotePolicy,
+    ) -> Result<reqwest::Response, StandardError> {
+        let status = self.status();
+        if !(status.is_client_error() || status.is_server_error()) {
+            return Ok(self);
+        }
+        let body = self.bytes().await?;
+        Err(StandardError::from_remote(status, &body, policy))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_remote_wire() {
+        let body = br#"{"code": "ER-0008", "status": 404, "values": {"user_id": "42"}, "message": "User 42 not found", "locale": "en_US"}"#;
+        let err = StandardError::from_remote(StatusCode::NOT_FOUND, body, RemotePolicy::default());
+        assert_eq!(err.err_code, "ER-0008");
+        assert_eq!(err.status_code, StatusCode::NOT_FOUND);
+        assert_eq!(err.render("hi_IN"), "उपयोगकर्ता 42 नहीं मिला");
+    }
+
+    #[test]
+    fn test_from_remote_problem() {
+        let body = br#"{"type": "about:blank", "title": "Conflict", "status": 409, "detail": "Seat 4A is taken", "code": "ER-SEAT-TAKEN", "values": {"seat": "4A"}}"#;
+        let err = StandardError::from_remote(StatusCode::CONFLICT, body, RemotePolicy::default());
+        assert_eq!(err.err_code, "ER-SEAT-TAKEN");
+        assert_eq!(err.values().get("seat"), Some(&"4A".to_string()));
+        assert_eq!(err.message(), "Seat 4A is taken");
+    }
+
+    #[test]
+    fn test_from_remote_legacy() {
+        let body = br#"{"detail": "Should be an integer"}"#;
+        let err = StandardError::from_remote(StatusCode::SERVICE_UNAVAILABLE, body, RemotePolicy::bad_gateway());
+        assert_eq!(err.err_code, "ER-REQWEST-REMOTE");
+        assert_eq!(err.status_code, StatusCode::BAD_GATEWAY);
+        assert_eq!(err.render("en_US"), "Upstream service error: Should be an integer");
+
+        let err = StandardError::from_remote(StatusCode::BAD_REQUEST, b"", RemotePolicy::bad_gateway());
+        assert_eq!(err.status_code, StatusCode::BAD_REQUEST);
+        assert_eq!(err.render("en_US"), "Upstream service error: 400 Bad Request");
+    }
+}
@@ -1,15 +1,125 @@
-use crate::StandardError;
+use crate::extras::formatter::{
+    error_body, errors_body, negotiated_error_body, negotiated_errors_body, ResponseContext,
+};
+use crate::extras::negotiate::Representation;
+use crate::{settings, Metadata, StandardError, StandardErrors};
+use axum::body::Body;
+use axum::extract::Request;
+use axum::http::header::{ACCEPT, CONTENT_LENGTH, CONTENT_TYPE, VARY};
+use axum::http::{HeaderValue, StatusCode};
+use axum::middleware::Next;
 use axum::response::IntoResponse;
-use axum::Json;
 use axum::response::Response;
-use serde_json::json;
+
+fn json_response(status: StatusCode, (content_type, body): (&'static str, String)) -> Response {
+    (
+        status,
+        [(CONTENT_TYPE, content_type)],
+        body
+    )
+        .into_response()
+}
+
+/// Swaps the body of an already built error response, keeping its status and headers.
+/// The body depends on `Accept` from here on, caches have to know
+fn replace_body(response: Response, (content_type, body): (&'static str, String)) -> Response {
+    let (mut parts, _) = response.into_parts();
+    parts.headers.insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+    parts.headers.append(VARY, HeaderValue::from_static("accept"));
+    parts.headers.remove(CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(body))
+}
 
 impl IntoResponse for StandardError {
     fn into_response(self) -> Response {
-        (
-            self.status_code,
-            Json(json!({"detail": self.message}))
-        )
-            .into_response()
+        let mut response = json_response(self.status_code, error_body(&self));
+        // kept for `finalize_errors`, which knows about the request
+        response.extensions_mut().insert(self);
+        response
+    }
+}
+
+impl IntoResponse for StandardErrors {
+    fn into_response(self) -> Response {
+        let mut response = json_response(self.status(), errors_body(&self));
+        response.extensions_mut().insert(self);
+        response
+    }
+}
+
+/// Middleware that re-renders error responses with what only the request knows: the request id,
+/// the path as the problem `instance` when the error has none, and the representation asked for
+/// in `Accept` (json, text/plain, html or xml).
+/// Add it with `.layer(axum::middleware::from_fn(standard_error::finalize_errors))`
+pub async fn finalize_errors(req: Request, next: Next) -> Response {
+    let headers = req.headers();
+    let request_id = headers
+        .get(settings.request_id_header.as_str())
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string());
+    let representation = headers
+        .get(ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .map(Representation::from_accept)
+        .unwrap_or(Representation::Json);
+    let path = req.uri().path().to_string();
+    let mut response = next.run(req).await;
+    let ctx = ResponseContext {
+        request_id,
+        ..ResponseContext::current()
+    };
+    if let Some(mut err) = response.extensions_mut().remove::<StandardError>() {
+        if err.instance_uri().is_none() {
+            err = err.instance(&path);
+        }
+        let body = negotiated_error_body(&err, representation, &ctx);
+        return replace_body(response, body);
+    }
+    if let Some(errs) = response.extensions_mut().remove::<StandardErrors>() {
+        let body = negotiated_errors_body(&errs, representation, &ctx);
+        return replace_body(response, body);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::get, Router};
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    async fn get_error(accept: &str) -> (StatusCode, String, String, String) {
+        let app = Router::new()
+            .route("/", get(|| async { Err::<(), _>(StandardError::new("ER-0008")) }))
+            .layer(axum::middleware::from_fn(finalize_errors));
+        let req = Request::builder().uri("/").header(ACCEPT, accept).body(Body::empty()).unwrap();
+        let response = app.oneshot(req).await.unwrap();
+        let status = response.status();
+        let content_type = response.headers()[CONTENT_TYPE].to_str().unwrap().to_string();
+        let vary = response.headers()[VARY].to_str().unwrap().to_string();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, content_type, vary, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_finalize_errors_negotiation() {
+        let (status, content_type, vary, body) = get_error("text/html").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(content_type, "text/html; charset=utf-8");
+        assert_eq!(vary, "accept");
+        assert!(body.contains("<h1>User not found</h1>"));
+
+        let (_, content_type, _, body) = get_error("text/plain").await;
+        assert_eq!(content_type, "text/plain; charset=utf-8");
+        assert_eq!(body, "404 ER-0008: User [user_id] not found\n");
+
+        let (_, content_type, _, body) = get_error("application/xml").await;
+        assert_eq!(content_type, "application/xml; charset=utf-8");
+        assert!(body.contains("<code>ER-0008</code><status>404</status>"));
+
+        let (status, _, _, body) = get_error("application/json").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(serde_json::from_str::<Value>(&body).unwrap()["detail"], "User [user_id] not found");
     }
 }
@@ -0,0 +1,20 @@
+use std::error::Error;
+use std::sync::Arc;
+
+use crate::StandardError;
+
+pub trait Source{
+    fn with_source<E>(self, e: E) -> Self
+    where
+        E: Error + Send + Sync + 'static;
+}
+
+impl Source for StandardError{
+    fn with_source<E>(mut self, e: E) -> Self
+    where
+        E: Error + Send + Sync + 'static,
+    {
+        self.source = Some(Arc::new(e));
+        self
+    }
+}
@@ -1,17 +1,17 @@
-use axum::http::StatusCode;
+use http::StatusCode;
 
 use crate::StandardError;
 
 
 
 pub trait Status{
-    fn code(&mut self, code: StatusCode) -> Self;
+    fn code(self, code: StatusCode) -> Self;
 }
 
 impl Status for StandardError{
-    fn code(&mut self, code: StatusCode) -> Self{
+    fn code(mut self, code: StatusCode) -> Self{
         self.status_code = code;
-        self.clone()
+        self
     }
 }
 
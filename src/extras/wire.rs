@@ -0,0 +1,128 @@
+use std::{borrow::Cow, collections::HashMap, error::Error, fmt, sync::Arc};
+
+use http::StatusCode;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{locale, StandardError};
+
+/// What a `StandardError` looks like on the wire, used by its `Serialize` / `Deserialize` impls
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StandardErrorWire {
+    pub code: String,
+    pub status: u16,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub values: HashMap<String, String>,
+    pub message: String,
+    pub locale: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub causes: Vec<CauseWire>,
+}
+
+/// One link of the source chain, outermost first. Causes that aren't `StandardError`s only have a message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CauseWire {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub values: HashMap<String, String>,
+    pub message: String,
+}
+
+/// Stand-in for a cause that wasn't a `StandardError` on the sending side
+#[derive(Debug, Clone)]
+pub struct RemoteError {
+    pub message: String,
+    source: Option<Arc<dyn Error + Send + Sync>>,
+}
+
+impl fmt::Display for RemoteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for RemoteError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source.as_deref().map(|e| e as &(dyn Error + 'static))
+    }
+}
+
+impl StandardErrorWire {
+    pub fn new(err: &StandardError, locale: &str) -> Self {
+        let mut causes = Vec::new();
+        let mut current = err.source.as_deref().map(|e| e as &(dyn Error + 'static));
+        while let Some(e) = current {
+            causes.push(match e.downcast_ref::<StandardError>() {
+                Some(standard) => CauseWire {
+                    code: Some(standard.err_code.to_string()),
+                    status: Some(standard.status_code.as_u16()),
+                    values: standard.values.clone(),
+                    message: standard.render(locale),
+                },
+                None => CauseWire {
+                    code: None,
+                    status: None,
+                    values: HashMap::new(),
+                    message: e.to_string(),
+                },
+            });
+            current = e.source();
+        }
+        StandardErrorWire {
+            code: err.err_code.to_string(),
+            status: err.status_code.as_u16(),
+            values: err.values.clone(),
+            message: err.render(locale),
+            locale: locale.to_string(),
+            causes,
+        }
+    }
+}
+
+pub(crate) fn rebuild(
+    code: String,
+    status: Option<u16>,
+    values: HashMap<String, String>,
+    message: String,
+    source: Option<Arc<dyn Error + Send + Sync>>,
+) -> Result<StandardError, String> {
+    let mut err = StandardError::new(Cow::Owned(code));
+    if let Some(status) = status {
+        err.status_code = StatusCode::from_u16(status).map_err(|e| e.to_string())?;
+    }
+    err.values = values;
+    err.occurrence_mut().fallback_message = Some(message);
+    err.source = source;
+    Ok(err)
+}
+
+impl TryFrom<StandardErrorWire> for StandardError {
+    type Error = String;
+
+    fn try_from(wire: StandardErrorWire) -> Result<Self, Self::Error> {
+        let mut source: Option<Arc<dyn Error + Send + Sync>> = None;
+        for cause in wire.causes.into_iter().rev() {
+            source = Some(match cause.code {
+                Some(code) => Arc::new(rebuild(code, cause.status, cause.values, cause.message, source)?),
+                None => Arc::new(RemoteError { message: cause.message, source }),
+            });
+        }
+        rebuild(wire.code, Some(wire.status), wire.values, wire.message, source)
+    }
+}
+
+impl Serialize for StandardError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        StandardErrorWire::new(self, &locale::get_current_locale()).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for StandardError {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        StandardErrorWire::deserialize(deserializer)?
+            .try_into()
+            .map_err(de::Error::custom)
+    }
+}
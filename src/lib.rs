@@ -1,56 +1,189 @@
-use axum::http::StatusCode;
 use lazy_static::lazy_static;
+use std::borrow::Cow;
 use std::collections::HashMap;
-use thiserror::Error;
+use std::fmt;
+use std::sync::Arc;
+
+extern crate self as standard_error;
 
 mod conf;
 mod loader;
 mod locale;
+pub mod codegen;
 pub mod extras;
 
+pub use http::StatusCode;
+pub use conf::{ResponseStyle, Settings};
 pub use locale::get_current_locale;
 pub use locale::set_current_locale;
+pub use locale::{
+    fallback_chain, match_locale, missing_translations, negotiate_locale, normalize_locale,
+    set_fallback_chain,
+};
+#[cfg(feature = "tokio")]
+pub use locale::with_locale;
 
 
 pub type StandardErrorMessages = HashMap<String, HashMap<String, String>>;
+pub type StandardErrorMetadata = HashMap<String, Arc<ErrorMetadata>>;
+pub use extras::aggregate::{ErrorItem, StandardErrors, StatusRule};
+pub use extras::context::{Context, OptionExt, ResultExt};
+pub use extras::negotiate::Representation;
+#[cfg(feature = "axum")]
+pub use extras::locale_layer::{LocaleLayer, LocaleService};
+#[cfg(feature = "axum")]
+pub use extras::response::finalize_errors;
+pub use extras::formatter::{
+    response_formatter, set_response_formatter, LegacyFormatter, ProblemFormatter, ResponseContext,
+    ResponseFormatter, TemplateFormatter,
+};
+pub use extras::remote::RemotePolicy;
+#[cfg(feature = "reqwest")]
+pub use extras::remote::ResponseExt;
 pub use extras::status::Status;
-pub use extras::interpolate::Interpolate;
+pub use extras::wire::{CauseWire, RemoteError, StandardErrorWire};
+#[cfg(feature = "fluent")]
+pub use extras::fluent::FluentCatalog;
+pub use extras::interpolate::{Interpolate, TemplateSyntax};
+#[cfg(feature = "actix")]
+pub use extras::actix::finalize_actix_errors;
+pub use extras::jsonrpc::{jsonrpc_mapping, set_jsonrpc_mapping, JsonRpcError, JsonRpcMapping};
+pub use extras::metadata::{ErrorMetadata, Metadata, Severity};
+pub use extras::source::Source;
+#[cfg(feature = "async-graphql")]
+pub use extras::graphql::{GraphqlLocale, GraphqlResultExt};
+#[cfg(feature = "tonic")]
+pub use extras::grpc::{grpc_code, grpc_locale, http_status};
+#[cfg(feature = "macros")]
+pub use standard_error_macros::{std_err, IntoStandardError};
+
+/// Implemented by the `Code` enum that `codegen::generate_codes` writes out
+pub trait ErrorCode {
+    fn as_str(&self) -> &'static str;
+}
 
-#[derive(Debug, Clone, Error)]
-#[error("Error {err_code} with status {status_code}")]
+#[derive(Debug, Clone)]
 pub struct StandardError {
-    pub err_code: String,
+    pub err_code: Cow<'static, str>,
     pub status_code: StatusCode,
+    metadata: Arc<ErrorMetadata>,
     values: HashMap<String, String>,
-    pub message: String,
+    source: Option<Arc<dyn std::error::Error + Send + Sync>>,
+    occurrence: Option<Box<Occurrence>>,
+}
+
+/// Data of this one error that most errors don't have, boxed to keep `StandardError` small
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Occurrence {
+    /// message rendered elsewhere, used when the local catalog doesn't know the code
+    pub(crate) fallback_message: Option<String>,
+    /// message given by the developer at the call site, wins over the catalog
+    pub(crate) message: Option<String>,
+    /// URI of this particular occurrence, the problem details `instance`
+    pub(crate) instance: Option<String>,
 }
 
 impl StandardError {
-    pub fn new(code: &str) -> Self {
+    pub fn new(code: impl Into<Cow<'static, str>>) -> Self {
+        let code = code.into();
+        let metadata = error_metadata.get(code.as_ref()).cloned().unwrap_or_default();
         StandardError {
-            err_code: code.to_string(),
-            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            err_code: code,
+            status_code: metadata.status.unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            metadata,
             values: HashMap::new(),
-            message: error_messages
-                .get(code)
-                .and_then(|locale_message| locale_message.get(&locale::get_current_locale()))
-                .map_or_else(|| format!("unknown error: {}", &code), |msg| msg.to_string()),
+            source: None,
+            occurrence: None,
         }
     }
+
+    pub fn from_code(code: impl ErrorCode) -> Self {
+        Self::new(code.as_str())
+    }
+
+    pub(crate) fn occurrence_mut(&mut self) -> &mut Occurrence {
+        self.occurrence.get_or_insert_with(Default::default)
+    }
+
+    pub(crate) fn instance_uri(&self) -> Option<&str> {
+        self.occurrence.as_ref()?.instance.as_deref()
+    }
+
+    pub fn render(&self, locale: &str) -> String {
+        let occurrence = self.occurrence.as_deref();
+        if let Some(message) = occurrence.and_then(|o| o.message.as_ref()) {
+            return message.clone();
+        }
+        error_messages
+            .get(self.err_code.as_ref())
+            .and_then(|translations| locale::localized(translations, &self.err_code, locale))
+            .map_or_else(
+                || match occurrence.and_then(|o| o.fallback_message.as_ref()) {
+                    Some(message) => message.clone(),
+                    None => format!("unknown error: {}", &self.err_code),
+                },
+                |(used, template)| {
+                    extras::interpolate::render_message(
+                        &self.metadata,
+                        &self.err_code,
+                        template,
+                        &self.values,
+                        used,
+                    )
+                },
+            )
+    }
+
+    pub fn message(&self) -> String {
+        self.render(&locale::get_current_locale())
+    }
+
+    pub fn values(&self) -> &HashMap<String, String> {
+        &self.values
+    }
+
+    pub fn metadata(&self) -> &ErrorMetadata {
+        &self.metadata
+    }
 }
 
+impl fmt::Display for StandardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Error {} with status {}", self.err_code, self.status_code)?;
+        if f.alternate() {
+            // {:#} prints the whole trail, handy for logs
+            let mut source = std::error::Error::source(self);
+            while let Some(e) = source {
+                match e.downcast_ref::<StandardError>() {
+                    Some(e) => write!(f, "\n  caused by: {}: {}", e.err_code, e.message())?,
+                    None => write!(f, "\n  caused by: {}", e)?,
+                }
+                source = e.source();
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for StandardError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source.as_deref().map(|e| e as &(dyn std::error::Error + 'static))
+    }
+}
 
 lazy_static! {
     pub static ref settings: conf::Settings = conf::Settings::new().expect("improperly configured");
     pub static ref error_messages: StandardErrorMessages =
         StandardError::load_error_messages().expect("error loading error messages");
+    pub static ref error_metadata: StandardErrorMetadata =
+        StandardError::load_error_metadata().expect("error loading error metadata");
 }
 
 #[cfg(test)]
 mod tests {
     use std::{collections::HashMap, num::ParseIntError};
-    use crate::extras::{status::Status, interpolate::Interpolate};
-    use axum::http::StatusCode;
+    use crate::extras::{status::Status, interpolate::Interpolate, metadata::Metadata, source::Source};
+    use http::StatusCode;
 
     use crate::StandardError;
 
@@ -66,7 +199,7 @@ mod tests {
 
         if let Err(e) = res{
             assert_eq!(e.status_code, StatusCode::INTERNAL_SERVER_ERROR);
-            assert_eq!(e.message, "Should be an integer".to_string())
+            assert_eq!(e.message(), "Should be an integer".to_string())
         }
 
         Ok(())
@@ -84,7 +217,7 @@ mod tests {
 
         if let Err(e) = res{
             assert_eq!(e.status_code, StatusCode::BAD_REQUEST);
-            assert_eq!(e.message, "Should be an integer".to_string())
+            assert_eq!(e.message(), "Should be an integer".to_string())
         }
 
         Ok(())
@@ -102,7 +235,7 @@ mod tests {
 
         if let Err(e) = res{
             assert_eq!(e.status_code, StatusCode::INTERNAL_SERVER_ERROR);
-            assert_eq!(e.message, "Should be an integer: invalid digit found in string".to_string())
+            assert_eq!(e.message(), "Should be an integer: invalid digit found in string".to_string())
         }
 
         Ok(())
@@ -123,7 +256,7 @@ mod tests {
 
         if let Err(e) = res{
             assert_eq!(e.status_code, StatusCode::INTERNAL_SERVER_ERROR);
-            assert_eq!(e.message, "Should be an integer - fname: ashu | lname: pednekar".to_string())
+            assert_eq!(e.message(), "Should be an integer - fname: ashu | lname: pednekar".to_string())
         }
 
         Ok(())
@@ -147,12 +280,222 @@ mod tests {
 
         if let Err(e) = res{
             assert_eq!(e.status_code, StatusCode::IM_A_TEAPOT);
-            assert_eq!(e.message, "Should be an integer - fname: ashu | lname: pednekar - invalid digit found in string".to_string())
+            assert_eq!(e.message(), "Should be an integer - fname: ashu | lname: pednekar - invalid digit found in string".to_string())
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_source() -> Result<(), StandardError> {
+        async fn foo(path: &str) -> Result<String, StandardError> {
+            Ok(std::fs::read_to_string(path)?)
+        }
+
+        let res = foo("does-not-exist.yaml").await;
+
+        if let Err(e) = res{
+            assert_eq!(e.err_code, "ER-IO-NOTFOUND".to_string());
+            let source = std::error::Error::source(&e).expect("source should be kept");
+            let io_err = source.downcast_ref::<std::io::Error>().expect("source should be an io::Error");
+            assert_eq!(io_err.kind(), std::io::ErrorKind::NotFound);
         }
 
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_catalog_defaults() -> Result<(), StandardError> {
+        let e = StandardError::new("ER-0008");
+        assert_eq!(e.status_code, StatusCode::NOT_FOUND);
+        assert_eq!(e.metadata().severity, Some(crate::Severity::Warning));
+        assert_eq!(e.metadata().retryable, Some(false));
+        assert_eq!(e.metadata().docs_url, Some("https://docs.example.com/errors/ER-0008".to_string()));
+
+        let e = StandardError::new("ER-0008")
+            .code(StatusCode::GONE)
+            .retryable(true);
+        assert_eq!(e.status_code, StatusCode::GONE);
+        assert_eq!(e.metadata().retryable, Some(true));
+        assert_eq!(crate::error_metadata["ER-0008"].retryable, Some(false));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "macros")]
+    #[tokio::test]
+    async fn test_derive() -> Result<(), StandardError> {
+        #[derive(Debug, crate::IntoStandardError)]
+        enum UserError {
+            #[standard_error(code = "ER-0008")]
+            NotFound { user_id: u64 },
+            #[standard_error(code = "ER-0005", status = 400)]
+            Parse(#[standard_error(source)] ParseIntError),
+            #[standard_error(code = "ER-0004", status = 418)]
+            Teapot,
+        }
+
+        let e: StandardError = UserError::NotFound { user_id: 42 }.into();
+        assert_eq!(e.status_code, StatusCode::NOT_FOUND);
+        assert_eq!(e.message(), "User 42 not found".to_string());
+
+        let e: StandardError = UserError::Parse("a".parse::<i32>().unwrap_err()).into();
+        assert_eq!(e.status_code, StatusCode::BAD_REQUEST);
+        assert_eq!(e.message(), "Should be an integer: invalid digit found in string".to_string());
+        assert!(std::error::Error::source(&e).and_then(|s| s.downcast_ref::<ParseIntError>()).is_some());
+
+        let e: StandardError = UserError::Teapot.into();
+        assert_eq!(e.status_code, StatusCode::IM_A_TEAPOT);
+
+        // fields named like the locals of the generated code
+        #[derive(Debug, crate::IntoStandardError)]
+        enum ParseError {
+            #[standard_error(code = "ER-0005")]
+            Parse {
+                #[standard_error(source)]
+                err: ParseIntError,
+                values: usize,
+            },
+        }
+
+        let e: StandardError = ParseError::Parse { err: "a".parse::<i32>().unwrap_err(), values: 3 }.into();
+        assert_eq!(e.message(), "Should be an integer: invalid digit found in string".to_string());
+        assert_eq!(e.values()["values"], "3");
+        assert!(std::error::Error::source(&e).and_then(|s| s.downcast_ref::<ParseIntError>()).is_some());
+
+        #[derive(Debug, crate::IntoStandardError)]
+        #[standard_error(code = "ER-0006", status = 400)]
+        struct NameError {
+            fname: String,
+            lname: String,
+            #[standard_error(skip)]
+            _password: String,
+        }
+
+        let e: StandardError = NameError {
+            fname: "ashu".to_string(),
+            lname: "pednekar".to_string(),
+            _password: "hunter2".to_string(),
+        }
+        .into();
+        assert_eq!(e.message(), "Should be an integer - fname: ashu | lname: pednekar".to_string());
+        assert!(!e.values().contains_key("_password"));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "macros")]
+    #[tokio::test]
+    async fn test_std_err_macro() -> Result<(), StandardError> {
+        struct User {
+            first: String,
+            last: String,
+        }
+        let user = User { first: "ashu".to_string(), last: "pednekar".to_string() };
+
+        let e = crate::std_err!("ER-0006", fname = user.first, lname = user.last; status = BAD_REQUEST);
+        assert_eq!(e.status_code, StatusCode::BAD_REQUEST);
+        assert_eq!(e.message(), "Should be an integer - fname: ashu | lname: pednekar".to_string());
+
+        let e = crate::std_err!("ER-0008", user_id = 42; status = 410);
+        assert_eq!(e.status_code, StatusCode::GONE);
+        assert_eq!(e.message(), "User 42 not found".to_string());
+
+        let e = crate::std_err!("ER-0005").interpolate_err("abc".to_string());
+        assert_eq!(e.message(), "Should be an integer: abc".to_string());
+
+        // the caller's locals, named like the ones the macro builds
+        let values = ["ashu"];
+        let err = "pednekar";
+        let e = crate::std_err!("ER-0006", fname = values[0], lname = err);
+        assert_eq!(e.message(), "Should be an integer - fname: ashu | lname: pednekar".to_string());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_context() -> Result<(), StandardError> {
+        use crate::{Context, OptionExt, ResultExt};
+
+        fn load(a: &str) -> Result<i32, StandardError> {
+            a.parse::<i32>().std_err("ER-0005")
+        }
+
+        let e = load("a").context("ER-0004").unwrap_err();
+        assert_eq!(e.err_code, "ER-0004".to_string());
+        let causes = e.causes();
+        assert_eq!(causes.len(), 1);
+        assert_eq!(causes[0].message(), "Should be an integer: invalid digit found in string".to_string());
+        assert!(format!("{:#}", e).contains("caused by: ER-0005: Should be an integer"));
+        assert!(format!("{:#}", e).contains("caused by: invalid digit found in string"));
+
+        // the inner status survives unless the outer code has its own
+        let e = load("a")
+            .map_err(|e| e.code(StatusCode::BAD_REQUEST))
+            .context("ER-0004")
+            .unwrap_err();
+        assert_eq!(e.status_code, StatusCode::BAD_REQUEST);
+        let e = load("a").context("ER-0008").unwrap_err();
+        assert_eq!(e.status_code, StatusCode::NOT_FOUND);
+
+        let e = "a".parse::<i32>()
+            .std_err_with(|e| StandardError::new("ER-0007").interpolate_err(e.to_string()))
+            .unwrap_err();
+        assert!(std::error::Error::source(&e).is_some());
+
+        let e = None::<i32>.ok_or_std("ER-0008").unwrap_err();
+        assert_eq!(e.status_code, StatusCode::NOT_FOUND);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_serde_round_trip() -> Result<(), StandardError> {
+        use crate::Context;
+
+        let e = "a".parse::<i32>()
+            .map_err(|e| StandardError::new("ER-0005").interpolate_err(e.to_string()).with_source(e))
+            .context("ER-0008")
+            .unwrap_err()
+            .interpolate_values([("user_id".to_string(), "42".to_string())].into());
+
+        let json = serde_json::to_value(&e)?;
+        assert_eq!(json["code"], "ER-0008");
+        assert_eq!(json["status"], 404);
+        assert_eq!(json["message"], "User 42 not found");
+        assert_eq!(json["causes"][0]["code"], "ER-0005");
+        assert_eq!(json["causes"][1]["message"], "invalid digit found in string");
+
+        let back: StandardError = serde_json::from_value(json.clone())?;
+        assert_eq!(back.err_code, "ER-0008");
+        assert_eq!(back.status_code, StatusCode::NOT_FOUND);
+        assert_eq!(back.values().get("user_id"), Some(&"42".to_string()));
+        assert_eq!(back.causes()[0].message(), "Should be an integer: invalid digit found in string");
+        assert_eq!(serde_json::to_value(&back)?, json);
+
+        // codes the local catalog doesn't know keep the sender's message
+        let remote: StandardError = serde_json::from_str(
+            r#"{"code": "ER-REMOTE-1", "status": 409, "message": "Already booked", "locale": "en_US"}"#,
+        )?;
+        assert_eq!(remote.status_code, StatusCode::CONFLICT);
+        assert_eq!(remote.message(), "Already booked");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_render_locale() -> Result<(), StandardError> {
+        let e = StandardError::new("ER-0005").interpolate_err("abc".to_string());
+
+        crate::set_current_locale("hi_IN");
+        assert_eq!(e.message(), "एक पूर्णांक होना चाहिए: abc".to_string());
+        assert_eq!(e.render("en_US"), "Should be an integer: abc".to_string());
+        crate::set_current_locale("en_US");
+        assert_eq!(e.message(), "Should be an integer: abc".to_string());
+
+        Ok(())
+    }
+
 
 
 }
@@ -1,38 +1,158 @@
-use crate::{conf::default_error_messages, settings, StandardError, StandardErrorMessages};
+use crate::{
+    conf::default_error_messages,
+    extras::interpolate::TemplateSyntax,
+    extras::metadata::{ErrorMetadata, Severity},
+    locale::normalize_locale,
+    settings, StandardError, StandardErrorMessages, StandardErrorMetadata,
+};
+use http::StatusCode;
 use serde_json::to_string_pretty;
 use serde_yaml::Value;
-use std::{collections::HashMap, fs::File, io::BufReader};
+use std::{collections::HashMap, fs::File, io::BufReader, sync::Arc};
 
-impl StandardError {
-    pub fn load_error_messages() -> Result<StandardErrorMessages, Box<dyn std::error::Error>> {
-        let file = File::open(&settings.error_yaml_file_path)?;
-        let reader = BufReader::new(file);
-        let yaml: Value = serde_yaml::from_reader(reader)?;
-        let mut messages: StandardErrorMessages = default_error_messages(); 
-        if let Some(errors) = yaml.get("errors").and_then(|v| v.as_sequence()) {
-            for error in errors {
-                if let Some(code) = error.get("code").and_then(|v| v.as_str()) {
-                    let mut locale_messages = HashMap::new();
-
-                    for (key, value) in error.as_mapping().unwrap() {
-                        if let Some(key_str) = key.as_str() {
-                            if key_str.starts_with("detail_") {
-                                if let Some(detail) = value.as_str() {
-                                    locale_messages.insert(
-                                        key_str.replace("detail_", "").to_string(),
-                                        detail.to_string(),
-                                    );
-                                }
+fn read_error_yaml() -> Result<Value, Box<dyn std::error::Error>> {
+    // a fluent only catalog doesn't need the yaml
+    #[cfg(feature = "fluent")]
+    if settings.error_ftl_dir.is_some() && !std::path::Path::new(&settings.error_yaml_file_path).exists() {
+        return Ok(Value::Null);
+    }
+    let file = File::open(&settings.error_yaml_file_path)?;
+    let reader = BufReader::new(file);
+    Ok(serde_yaml::from_reader(reader)?)
+}
+
+pub(crate) fn error_messages_from_yaml(yaml: &Value) -> StandardErrorMessages {
+    let mut messages: StandardErrorMessages = default_error_messages(); 
+    if let Some(errors) = yaml.get("errors").and_then(|v| v.as_sequence()) {
+        for error in errors {
+            if let Some(code) = error.get("code").and_then(|v| v.as_str()) {
+                let mut locale_messages = HashMap::new();
+
+                for (key, value) in error.as_mapping().unwrap() {
+                    if let Some(key_str) = key.as_str() {
+                        if key_str.starts_with("detail_") {
+                            if let Some(detail) = value.as_str() {
+                                locale_messages.insert(
+                                    normalize_locale(&key_str.replace("detail_", "")),
+                                    detail.to_string(),
+                                );
+                            }
+                        }
+                    }
+                }
+                messages.insert(code.to_string(), locale_messages);
+            }
+        }
+    }
+    messages
+}
+
+/// `<prefix><locale>` keys of an entry, by normalized locale
+fn localized(error: &Value, prefix: &str) -> HashMap<String, String> {
+    error
+        .as_mapping()
+        .map(|mapping| {
+            mapping
+                .iter()
+                .filter_map(|(k, v)| {
+                    let locale = k.as_str()?.strip_prefix(prefix)?;
+                    Some((normalize_locale(locale), v.as_str()?.to_string()))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub(crate) fn error_metadata_from_yaml(yaml: &Value) -> Result<StandardErrorMetadata, Box<dyn std::error::Error>> {
+    let mut metadata: StandardErrorMetadata = HashMap::new();
+    let catalog_syntax = match yaml.get("syntax").and_then(|v| v.as_str()) {
+        Some(syntax) => syntax.parse::<TemplateSyntax>()?,
+        None => TemplateSyntax::default(),
+    };
+    if let Some(errors) = yaml.get("errors").and_then(|v| v.as_sequence()) {
+        for error in errors {
+            if let Some(code) = error.get("code").and_then(|v| v.as_str()) {
+                let status = match error.get("status").and_then(|v| v.as_u64()) {
+                    Some(status) => Some(StatusCode::from_u16(u16::try_from(status)?)?),
+                    None => None,
+                };
+                let severity = match error.get("severity").and_then(|v| v.as_str()) {
+                    Some(severity) => Some(severity.parse::<Severity>()?),
+                    None => None,
+                };
+                let titles = localized(error, "title_");
+                let hints = localized(error, "hint_");
+                let syntax = match error.get("syntax").and_then(|v| v.as_str()) {
+                    Some(syntax) => syntax.parse::<TemplateSyntax>()?,
+                    None => catalog_syntax,
+                };
+                #[cfg(feature = "icu")]
+                let mut icu_templates = HashMap::new();
+                #[cfg(feature = "icu")]
+                if syntax == TemplateSyntax::Icu {
+                    for (key, value) in error.as_mapping().into_iter().flatten() {
+                        if let (Some(key), Some(template)) = (key.as_str(), value.as_str()) {
+                            if let Some(locale) = key.strip_prefix("detail_") {
+                                let parsed = crate::extras::message_format::parse(template)
+                                    .map_err(|e| format!("{}.{}: {}", code, key, e))?;
+                                icu_templates.insert(normalize_locale(locale), Arc::new(parsed));
                             }
                         }
                     }
-                    messages.insert(code.to_string(), locale_messages);
                 }
+                let meta = match error.get("meta").and_then(|v| v.as_mapping()) {
+                    Some(meta) => meta
+                        .iter()
+                        .filter_map(|(k, v)| Some((k.as_str()?.to_string(), serde_json::to_value(v).ok()?)))
+                        .collect(),
+                    None => HashMap::new(),
+                };
+                metadata.insert(
+                    code.to_string(),
+                    Arc::new(ErrorMetadata {
+                        status,
+                        severity,
+                        retryable: error.get("retryable").and_then(|v| v.as_bool()),
+                        docs_url: error.get("docs_url").and_then(|v| v.as_str()).map(|s| s.to_string()),
+                        jsonrpc_code: error.get("jsonrpc_code").and_then(|v| v.as_i64()),
+                        titles,
+                        hints,
+                        meta,
+                        syntax,
+                        #[cfg(feature = "icu")]
+                        icu_templates,
+                    }),
+                );
             }
         }
+    }
+    Ok(metadata)
+}
+
+impl StandardError {
+    pub fn load_error_messages() -> Result<StandardErrorMessages, Box<dyn std::error::Error>> {
+        let yaml = read_error_yaml()?;
+        #[allow(unused_mut)]
+        let mut messages = error_messages_from_yaml(&yaml);
+        #[cfg(feature = "fluent")]
+        if let Some(catalog) = crate::extras::fluent::fluent_catalog.as_ref() {
+            catalog.merge_messages(&mut messages);
+        }
         log::debug!("Loaded error messages: {}", to_string_pretty(&messages)?);
         Ok(messages)
     }
+
+    pub fn load_error_metadata() -> Result<StandardErrorMetadata, Box<dyn std::error::Error>> {
+        let yaml = read_error_yaml()?;
+        #[allow(unused_mut)]
+        let mut metadata = error_metadata_from_yaml(&yaml)?;
+        #[cfg(feature = "fluent")]
+        if let Some(catalog) = crate::extras::fluent::fluent_catalog.as_ref() {
+            catalog.merge_metadata(&mut metadata);
+        }
+        log::debug!("Loaded error metadata for {} codes", metadata.len());
+        Ok(metadata)
+    }
 }
 
 #[cfg(test)]
@@ -44,4 +164,64 @@ mod tests {
         env_logger::init();
         StandardError::load_error_messages().unwrap();
     }
+
+    #[tokio::test]
+    async fn test_error_metadata_loader() {
+        let metadata = StandardError::load_error_metadata().unwrap();
+        let meta = metadata.get("ER-0008").unwrap();
+        assert_eq!(meta.status, Some(StatusCode::NOT_FOUND));
+        assert_eq!(meta.severity, Some(Severity::Warning));
+        assert_eq!(meta.retryable, Some(false));
+        assert_eq!(meta.meta.get("owner"), Some(&serde_json::json!("users-team")));
+    }
+
+    #[test]
+    fn test_hints() {
+        let yaml: Value = serde_yaml::from_str(
+            r#"
+errors:
+  - code: ER-CART
+    detail_en_US: "Too many items"
+    hint_en-us: "Remove some items and try again"
+"#,
+        )
+        .unwrap();
+        let metadata = error_metadata_from_yaml(&yaml).unwrap();
+        assert_eq!(metadata["ER-CART"].hints["en_US"], "Remove some items and try again");
+    }
+
+    #[test]
+    fn test_template_syntax() {
+        let yaml: Value = serde_yaml::from_str(
+            r#"
+syntax: icu
+errors:
+  - code: ER-CART
+    detail_en_US: "{count, plural, one {# item} other {# items}}"
+"#,
+        )
+        .unwrap();
+        #[cfg(feature = "icu")]
+        {
+            let metadata = error_metadata_from_yaml(&yaml).unwrap();
+            assert_eq!(metadata["ER-CART"].syntax, TemplateSyntax::Icu);
+            // rendered from the template parsed at load, not the text passed in
+            let values = [("count".to_string(), "3".to_string())].into();
+            let message = crate::extras::interpolate::render_message(&metadata["ER-CART"], "ER-CART", "{", &values, "en_US");
+            assert_eq!(message, "3 items");
+            let invalid: Value = serde_yaml::from_str(
+                r#"
+errors:
+  - code: ER-CART
+    syntax: icu
+    detail_en_US: "{count, plural, one {# item}}"
+"#,
+            )
+            .unwrap();
+            let e = error_metadata_from_yaml(&invalid).unwrap_err();
+            assert!(e.to_string().starts_with("ER-CART.detail_en_US"));
+        }
+        #[cfg(not(feature = "icu"))]
+        assert!(error_metadata_from_yaml(&yaml).is_err());
+    }
 }
@@ -1,16 +1,302 @@
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::env;
+#[cfg(feature = "tokio")]
+use std::future::Future;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::RwLock;
+
+use lazy_static::lazy_static;
+
+use crate::{error_messages, settings};
+
+/// cache generation and the chains built in it
+type ChainCache = (usize, HashMap<String, Rc<[String]>>);
 
 thread_local! {
     static LOCALE: RefCell<String> = RefCell::new(env::var("DEFAULT_LOCALE").unwrap_or("en_US".to_string()));
+    /// fallback chains of catalog locales, dropped when `CHAINS_GENERATION` moves on
+    static CHAINS: RefCell<ChainCache> = RefCell::new((0, HashMap::new()));
+}
+
+/// bumped by `set_fallback_chain`, so every thread rebuilds its cached chains
+static CHAINS_GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+lazy_static! {
+    /// every locale with at least one message in the catalog
+    static ref catalog_locales: Vec<String> = {
+        let mut locales: Vec<String> = error_messages
+            .values()
+            .flat_map(|messages| messages.keys().cloned())
+            .collect();
+        locales.sort();
+        locales.dedup();
+        locales
+    };
+    static ref default_locale: String = normalize_locale(&settings.default_locale);
+    static ref fallback_chains: RwLock<HashMap<String, Vec<String>>> = RwLock::new(HashMap::new());
+    /// code -> catalog locale it has no message in -> fallbacks, fixed once the catalog is loaded
+    static ref fallback_counts: HashMap<String, HashMap<String, AtomicU64>> = error_messages
+        .iter()
+        .map(|(code, translations)| {
+            let missing = catalog_locales
+                .iter()
+                .filter(|locale| !translations.contains_key(*locale))
+                .map(|locale| (locale.clone(), AtomicU64::new(0)))
+                .collect();
+            (code.clone(), missing)
+        })
+        .collect();
+}
+
+#[cfg(feature = "tokio")]
+tokio::task_local! {
+    static TASK_LOCALE: String;
 }
 
+/// Sets the locale of the current thread, only meant for sync code. Inside `with_locale` the
+/// task's locale wins
 pub fn set_current_locale(locale: &str) {
     LOCALE.with(|s| {
         *s.borrow_mut() = locale.to_string();
     })
 }
 
+/// The `with_locale` scope this runs in, else the thread's locale
 pub fn get_current_locale() -> String {
+    #[cfg(feature = "tokio")]
+    if let Ok(locale) = TASK_LOCALE.try_with(|locale| locale.clone()) {
+        return locale;
+    }
     LOCALE.with(|s| s.borrow().clone())
 }
+
+/// Runs `fut` with `locale` as the current locale. Unlike `set_current_locale` this follows the
+/// future when the runtime moves it to another worker thread, and ends with it
+#[cfg(feature = "tokio")]
+pub async fn with_locale<F: Future>(locale: impl Into<String>, fut: F) -> F::Output {
+    TASK_LOCALE.scope(locale.into(), fut).await
+}
+
+/// BCP 47 tags to the catalog's form: `en-us`, `EN_US` and `en_US` all become `en_US`,
+/// `zh-hant-tw` becomes `zh_Hant_TW`
+pub fn normalize_locale(tag: &str) -> String {
+    tag.trim()
+        .split(['-', '_'])
+        .filter(|part| !part.is_empty())
+        .enumerate()
+        .map(|(i, part)| match part.len() {
+            _ if i == 0 => part.to_ascii_lowercase(),
+            2 if part.chars().all(|c| c.is_ascii_alphabetic()) => part.to_ascii_uppercase(),
+            4 if part.chars().all(|c| c.is_ascii_alphabetic()) => {
+                part[..1].to_ascii_uppercase() + &part[1..].to_ascii_lowercase()
+            }
+            _ => part.to_ascii_lowercase(),
+        })
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// Locales tried after `locale` itself and its parents (`hi_IN` -> `hi`), before the default one
+pub fn set_fallback_chain(locale: &str, fallbacks: &[&str]) {
+    fallback_chains
+        .write()
+        .expect("fallback chains lock poisoned")
+        .insert(normalize_locale(locale), fallbacks.iter().map(|l| normalize_locale(l)).collect());
+    CHAINS_GENERATION.fetch_add(1, Ordering::Release);
+}
+
+/// `locale`, its parents, the chain set with `set_fallback_chain`, then `DEFAULT_LOCALE`
+pub fn fallback_chain(locale: &str) -> Vec<String> {
+    let locale = normalize_locale(locale);
+    let mut chain = vec![locale.clone()];
+    let mut parts: Vec<&str> = locale.split('_').collect();
+    while parts.len() > 1 {
+        parts.pop();
+        chain.push(parts.join("_"));
+    }
+    if let Some(fallbacks) = fallback_chains.read().expect("fallback chains lock poisoned").get(&locale) {
+        chain.extend(fallbacks.iter().cloned());
+    }
+    chain.push(default_locale.clone());
+    let mut deduped = Vec::with_capacity(chain.len());
+    for l in chain {
+        if !deduped.contains(&l) {
+            deduped.push(l);
+        }
+    }
+    deduped
+}
+
+/// `fallback_chain` without the allocations on the render path. Only catalog locales are
+/// cached, so whatever locales callers pass in can't grow the cache
+fn cached_fallback_chain(locale: &str) -> Rc<[String]> {
+    CHAINS.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        let generation = CHAINS_GENERATION.load(Ordering::Acquire);
+        if cache.0 != generation {
+            *cache = (generation, HashMap::new());
+        }
+        if let Some(chain) = cache.1.get(locale) {
+            return chain.clone();
+        }
+        let locale = normalize_locale(locale);
+        if let Some(chain) = cache.1.get(&locale) {
+            return chain.clone();
+        }
+        let chain: Rc<[String]> = fallback_chain(&locale).into();
+        if catalog_locales.contains(&locale) {
+            cache.1.insert(locale, chain.clone());
+        }
+        chain
+    })
+}
+
+fn resolve_in<'a>(translations: &'a HashMap<String, String>, chain: &[String]) -> Option<(&'a str, &'a str)> {
+    chain
+        .iter()
+        .find_map(|l| translations.get_key_value(l))
+        .or_else(|| translations.iter().min_by(|a, b| a.0.cmp(b.0)))
+        .map(|(l, text)| (l.as_str(), text.as_str()))
+}
+
+/// The first translation along `locale`'s fallback chain, else any translation there is.
+/// Returns the locale it came from as well
+pub(crate) fn resolve<'a>(translations: &'a HashMap<String, String>, locale: &str) -> Option<(&'a str, &'a str)> {
+    resolve_in(translations, &cached_fallback_chain(locale))
+}
+
+/// Like `resolve`, but counts and logs every time `code` isn't translated to a catalog locale
+pub(crate) fn localized<'a>(
+    translations: &'a HashMap<String, String>,
+    code: &str,
+    locale: &str,
+) -> Option<(&'a str, &'a str)> {
+    let chain = cached_fallback_chain(locale);
+    let (used, text) = resolve_in(translations, &chain)?;
+    let requested = &chain[0];
+    if used != requested {
+        if let Some(count) = fallback_counts.get(code).and_then(|missing| missing.get(requested)) {
+            match count.fetch_add(1, Ordering::Relaxed) {
+                0 => log::warn!("no {} message for {}, falling back to {}", requested, code, used),
+                _ => log::debug!("no {} message for {}, falling back to {}", requested, code, used),
+            }
+        }
+    }
+    Some((used, text))
+}
+
+/// How many times each `(code, locale)` had to fall back to another catalog locale since startup
+pub fn missing_translations() -> HashMap<(String, String), u64> {
+    fallback_counts
+        .iter()
+        .flat_map(|(code, missing)| {
+            missing.iter().filter_map(move |(locale, count)| {
+                let count = count.load(Ordering::Relaxed);
+                (count > 0).then(|| ((code.clone(), locale.clone()), count))
+            })
+        })
+        .collect()
+}
+
+/// The catalog locale for a tag like `hi-IN`, `hi_in` or just `hi`
+pub fn match_locale(tag: &str) -> Option<String> {
+    let tag = normalize_locale(tag);
+    let language = tag.split('_').next().unwrap_or_default();
+    catalog_locales
+        .iter()
+        .find(|locale| **locale == tag)
+        .or_else(|| {
+            catalog_locales.iter().find(|locale| {
+                locale
+                    .split('_')
+                    .next()
+                    .is_some_and(|l| !language.is_empty() && l.eq_ignore_ascii_case(language))
+            })
+        })
+        .cloned()
+}
+
+/// The catalog locale that best matches an `Accept-Language` header, honouring q-values
+pub fn negotiate_locale(accept_language: &str) -> Option<String> {
+    let mut tags: Vec<(&str, f32)> = accept_language
+        .split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.split(';');
+            let tag = parts.next()?.trim();
+            let q = parts
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            (!tag.is_empty() && tag != "*" && q > 0.0).then_some((tag, q))
+        })
+        .collect();
+    // stable, so equal q-values keep the client's order
+    tags.sort_by(|a, b| b.1.total_cmp(&a.1));
+    tags.into_iter().find_map(|(tag, _)| match_locale(tag))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_locale() {
+        assert_eq!(normalize_locale("en-US"), "en_US");
+        assert_eq!(normalize_locale("en_us"), "en_US");
+        assert_eq!(normalize_locale("EN-us"), "en_US");
+        assert_eq!(normalize_locale("zh-hant-tw"), "zh_Hant_TW");
+        assert_eq!(normalize_locale("es-419"), "es_419");
+    }
+
+    #[test]
+    fn test_fallback_chain() {
+        assert_eq!(fallback_chain("hi-IN"), vec!["hi_IN", "hi", "en_US"]);
+        set_fallback_chain("pt-BR", &["pt_PT", "es"]);
+        assert_eq!(fallback_chain("pt_br"), vec!["pt_BR", "pt", "pt_PT", "es", "en_US"]);
+    }
+
+    #[test]
+    fn test_render_fallback() {
+        let err = crate::StandardError::new("ER-0004");
+        assert_eq!(err.render("hi-in"), "एक पूर्णांक होना चाहिए");
+        assert_eq!(err.render("fr_FR"), "Should be an integer");
+        // only in english, whatever the default locale
+        assert_eq!(crate::StandardError::new("ER-IO-NOTFOUND").render("hi_IN"), "File not found: [err]");
+        assert_eq!(crate::StandardError::new("ER-IO-NOTFOUND").render("hi-in"), "File not found: [err]");
+        let missing = missing_translations();
+        assert_eq!(missing.get(&("ER-IO-NOTFOUND".to_string(), "hi_IN".to_string())), Some(&2));
+        // locales the catalog doesn't have aren't counted, callers can pass anything
+        assert_eq!(missing.get(&("ER-0004".to_string(), "fr_FR".to_string())), None);
+    }
+
+    #[test]
+    fn test_negotiate_locale() {
+        assert_eq!(negotiate_locale("hi-IN,en;q=0.8"), Some("hi_IN".to_string()));
+        assert_eq!(negotiate_locale("fr-FR, hi;q=0.5, en-US;q=0.9"), Some("en_US".to_string()));
+        assert_eq!(negotiate_locale("en-us;q=0, hi"), Some("hi_IN".to_string()));
+        assert_eq!(negotiate_locale("fr-FR, *;q=0.1"), None);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_with_locale() {
+        let tasks: Vec<_> = (0..32)
+            .map(|i| {
+                let locale = if i % 2 == 0 { "hi_IN" } else { "en_US" };
+                tokio::spawn(with_locale(locale, async move {
+                    for _ in 0..10 {
+                        tokio::task::yield_now().await;
+                        assert_eq!(get_current_locale(), locale);
+                    }
+                    crate::StandardError::new("ER-0004").message()
+                }))
+            })
+            .collect();
+        for (i, task) in tasks.into_iter().enumerate() {
+            let expected = if i % 2 == 0 { "एक पूर्णांक होना चाहिए" } else { "Should be an integer" };
+            assert_eq!(task.await.unwrap(), expected);
+        }
+    }
+}
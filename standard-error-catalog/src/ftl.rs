@@ -0,0 +1,85 @@
+//! Just enough of Fluent for build time: the message ids of an `errors.ftl` and the `$variables`
+//! they use, for the `std_err!` check and `codegen`
+use std::{
+    collections::BTreeSet,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+pub struct FtlMessage {
+    pub id: String,
+    /// the value as written, continuation lines joined with a space
+    pub text: String,
+    /// `$variables` of the value and of the attributes
+    pub variables: BTreeSet<String>,
+}
+
+/// Messages of an ftl resource, terms (`-brand = ...`) and comments are skipped
+pub fn ftl_messages(source: &str) -> Vec<FtlMessage> {
+    let mut messages: Vec<FtlMessage> = Vec::new();
+    // in a message, and still in its value rather than its attributes
+    let (mut in_message, mut in_value) = (false, false);
+    for line in source.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if !line.starts_with([' ', '\t']) {
+            (in_message, in_value) = (false, false);
+            let Some((id, value)) = line.split_once('=') else { continue };
+            let id = id.trim();
+            let is_id = id.starts_with(|c: char| c.is_ascii_alphabetic())
+                && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            if is_id {
+                messages.push(FtlMessage {
+                    id: id.to_string(),
+                    text: value.trim().to_string(),
+                    variables: ftl_variables(value),
+                });
+                (in_message, in_value) = (true, true);
+            }
+            continue;
+        }
+        let Some(message) = messages.last_mut().filter(|_| in_message) else { continue };
+        message.variables.extend(ftl_variables(line));
+        // attributes come after the value
+        in_value &= !line.trim_start().starts_with('.');
+        if in_value {
+            if !message.text.is_empty() {
+                message.text.push(' ');
+            }
+            message.text.push_str(line.trim());
+        }
+    }
+    messages
+}
+
+fn ftl_variables(line: &str) -> BTreeSet<String> {
+    let mut found = BTreeSet::new();
+    let mut rest = line;
+    while let Some(start) = rest.find('$') {
+        rest = &rest[start + 1..];
+        let end = rest
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '-' && c != '_')
+            .unwrap_or(rest.len());
+        if rest.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            found.insert(rest[..end].to_string());
+        }
+        rest = &rest[end..];
+    }
+    found
+}
+
+/// `(locale, path, messages)` of every `<dir>/<locale>/errors.ftl`, the locale as the directory is named
+pub fn ftl_catalog(dir: &Path) -> io::Result<Vec<(String, PathBuf, Vec<FtlMessage>)>> {
+    let mut resources = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path().join("errors.ftl");
+        if path.is_file() {
+            let messages = ftl_messages(&fs::read_to_string(&path)?);
+            resources.push((entry.file_name().to_string_lossy().into_owned(), path, messages));
+        }
+    }
+    resources.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(resources)
+}
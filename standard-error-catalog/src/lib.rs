@@ -0,0 +1,6 @@
+//! What standard-error and its macros both need to know about the catalog, kept in one place so
+//! the runtime and the `std_err!` check can't drift apart
+mod builtin;
+pub mod ftl;
+
+pub use builtin::BUILTIN_MESSAGES;
@@ -0,0 +1,325 @@
+use std::{collections::BTreeSet, env, fs, path::PathBuf};
+
+use serde_yaml::Value;
+use standard_error_catalog::ftl::{ftl_catalog, FtlMessage};
+use standard_error_catalog::BUILTIN_MESSAGES;
+
+pub struct Catalog {
+    /// the yaml and ftl files read, the expansion depends on them
+    pub files: Vec<PathBuf>,
+    yaml: Value,
+    /// `(locale, path, messages)` of each `<ERROR_FTL_DIR>/<locale>/errors.ftl`
+    ftl: Vec<(String, PathBuf, Vec<FtlMessage>)>,
+}
+
+/// Relative paths are taken from the crate root, like the runtime does from the working directory
+fn manifest_path(path: &str) -> Option<PathBuf> {
+    let path = PathBuf::from(path);
+    match path.is_relative() {
+        true => Some(PathBuf::from(env::var("CARGO_MANIFEST_DIR").ok()?).join(path)),
+        false => Some(path),
+    }
+}
+
+impl Catalog {
+    /// Same lookup as the runtime loader, `ERROR_YAML_FILE_PATH` or `errors.yaml` at the crate root,
+    /// plus the `ERROR_FTL_DIR` resources. Returns `None` when there's no catalog to check against
+    /// at build time
+    pub fn load() -> Option<Catalog> {
+        let yaml_path = manifest_path(&env::var("ERROR_YAML_FILE_PATH").unwrap_or("errors.yaml".to_string()))?;
+        let ftl = match env::var("ERROR_FTL_DIR") {
+            Ok(dir) => ftl_catalog(&manifest_path(&dir)?).ok()?,
+            Err(_) => Vec::new(),
+        };
+        let mut files: Vec<PathBuf> = ftl.iter().map(|(_, path, _)| path.clone()).collect();
+        let yaml = match fs::read_to_string(&yaml_path) {
+            Ok(yaml) => {
+                files.insert(0, yaml_path);
+                serde_yaml::from_str(&yaml).ok()?
+            }
+            // a fluent only catalog doesn't need the yaml
+            Err(_) if !ftl.is_empty() => Value::Null,
+            Err(_) => return None,
+        };
+        Some(Catalog { files, yaml, ftl })
+    }
+
+    #[cfg(test)]
+    pub fn from_str(yaml: &str) -> Catalog {
+        Catalog { files: Vec::new(), yaml: serde_yaml::from_str(yaml).unwrap(), ftl: Vec::new() }
+    }
+
+    #[cfg(test)]
+    pub fn with_ftl(mut self, locale: &str, ftl: &str) -> Catalog {
+        let path = PathBuf::from(locale).join("errors.ftl");
+        self.ftl.push((locale.to_string(), path, standard_error_catalog::ftl::ftl_messages(ftl)));
+        self
+    }
+
+    /// `(where, placeholders)` pairs for a code, `None` if the code isn't in the catalog.
+    /// A code in the ftl files is checked against those only, like the runtime renders it.
+    /// Built-in codes the catalog doesn't override are checked against their default message
+    fn templates(&self, code: &str) -> Option<Vec<(String, BTreeSet<String>)>> {
+        let ftl: Vec<(String, BTreeSet<String>)> = self
+            .ftl
+            .iter()
+            .filter_map(|(_, path, messages)| {
+                let message = messages.iter().find(|message| message.id == code)?;
+                Some((format!("{} in {}", code, path.display()), message.variables.clone()))
+            })
+            .collect();
+        if !ftl.is_empty() {
+            return Some(ftl);
+        }
+        let error = self
+            .yaml
+            .get("errors")
+            .and_then(|v| v.as_sequence())
+            .and_then(|errors| {
+                errors
+                    .iter()
+                    .find(|error| error.get("code").and_then(|v| v.as_str()) == Some(code))
+            });
+        let Some(error) = error else {
+            let (_, template) = BUILTIN_MESSAGES.iter().find(|(builtin, _)| *builtin == code)?;
+            return Some(vec![(format!("{}.detail_en_US", code), placeholders(template))]);
+        };
+        let syntax = error
+            .get("syntax")
+            .or_else(|| self.yaml.get("syntax"))
+            .and_then(|v| v.as_str());
+        Some(
+            error
+                .as_mapping()?
+                .iter()
+                .filter_map(|(key, value)| {
+                    let key = key.as_str().filter(|key| key.starts_with("detail_"))?;
+                    let template = value.as_str()?;
+                    let placeholders = match syntax {
+                        Some("icu") => icu_arguments(template),
+                        _ => placeholders(template),
+                    };
+                    Some((format!("{}.{}", code, key), placeholders))
+                })
+                .collect(),
+        )
+    }
+
+    /// Checks that the code exists and that `names` are exactly the placeholders of every locale.
+    /// `err` may be left out, it's usually filled by `interpolate_err`
+    pub fn check(&self, code: &str, names: &BTreeSet<String>) -> Result<(), String> {
+        let templates = match self.templates(code) {
+            Some(templates) => templates,
+            None => {
+                let files: Vec<String> = self.files.iter().map(|path| path.display().to_string()).collect();
+                return Err(format!("unknown error code `{}`, it isn't in {}", code, files.join(", ")));
+            }
+        };
+        for (location, placeholders) in templates {
+            if let Some(missing) = placeholders
+                .iter()
+                .find(|p| p.as_str() != "err" && !names.contains(*p))
+            {
+                return Err(format!("missing value for `{}` used in {}", missing, location));
+            }
+            if let Some(extra) = names.iter().find(|n| !placeholders.contains(*n)) {
+                return Err(format!("`{}` isn't a placeholder of {}", extra, location));
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn placeholders(template: &str) -> BTreeSet<String> {
+    let mut found = BTreeSet::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('[') {
+        rest = &rest[start + 1..];
+        match rest.find([']', '[']) {
+            Some(end) if rest[end..].starts_with(']') => {
+                found.insert(rest[..end].to_string());
+                rest = &rest[end + 1..];
+            }
+            _ => continue,
+        }
+    }
+    found
+}
+
+/// Argument names of an ICU MessageFormat template, `{name}` and `{name, plural, ...}`.
+/// Plural and select case bodies are messages of their own, only the arguments in them count
+pub fn icu_arguments(template: &str) -> BTreeSet<String> {
+    let chars: Vec<char> = template.chars().collect();
+    let mut found = BTreeSet::new();
+    icu_message(&chars, &mut 0, &mut found);
+    found
+}
+
+fn skip_whitespace(chars: &[char], pos: &mut usize) {
+    while chars.get(*pos).is_some_and(|c| c.is_whitespace()) {
+        *pos += 1;
+    }
+}
+
+fn icu_token(chars: &[char], pos: &mut usize) -> String {
+    skip_whitespace(chars, pos);
+    let start = *pos;
+    while chars
+        .get(*pos)
+        .is_some_and(|c| !c.is_whitespace() && !matches!(c, '{' | '}' | ','))
+    {
+        *pos += 1;
+    }
+    chars[start..*pos].iter().collect()
+}
+
+/// Walks a message up to the `}` closing it, or the end of the template
+fn icu_message(chars: &[char], pos: &mut usize, found: &mut BTreeSet<String>) {
+    while let Some(&c) = chars.get(*pos) {
+        *pos += 1;
+        match c {
+            // quoted literal, `'{'` isn't an argument
+            '\'' if matches!(chars.get(*pos), Some('{' | '}' | '#')) => {
+                while let Some(&q) = chars.get(*pos) {
+                    *pos += 1;
+                    if q == '\'' {
+                        break;
+                    }
+                }
+            }
+            '{' => icu_argument(chars, pos, found),
+            '}' => return,
+            _ => {}
+        }
+    }
+}
+
+/// Everything after an argument's `{`, up to and including its `}`
+fn icu_argument(chars: &[char], pos: &mut usize, found: &mut BTreeSet<String>) {
+    let name = icu_token(chars, pos);
+    if !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        found.insert(name);
+    }
+    skip_whitespace(chars, pos);
+    if chars.get(*pos) != Some(&',') {
+        return icu_message(chars, pos, found);
+    }
+    *pos += 1;
+    match icu_token(chars, pos).as_str() {
+        "plural" | "selectordinal" | "select" => loop {
+            // selectors, `offset:` and the case bodies
+            skip_whitespace(chars, pos);
+            match chars.get(*pos) {
+                Some('{') => {
+                    *pos += 1;
+                    icu_message(chars, pos, found);
+                }
+                Some('}') => {
+                    *pos += 1;
+                    return;
+                }
+                Some(',') => *pos += 1,
+                Some(_) => {
+                    icu_token(chars, pos);
+                }
+                None => return,
+            }
+        },
+        // number, date and friends, their style can't hold arguments
+        _ => {
+            let mut depth = 0;
+            while let Some(&c) = chars.get(*pos) {
+                *pos += 1;
+                match c {
+                    '{' => depth += 1,
+                    '}' if depth == 0 => return,
+                    '}' => depth -= 1,
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> BTreeSet<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_placeholders() {
+        assert_eq!(
+            placeholders("Should be an integer - fname: [fname] | lname: [lname] - [err]"),
+            names(&["err", "fname", "lname"])
+        );
+        assert_eq!(placeholders("no [placeholders here"), names(&[]));
+        assert_eq!(
+            icu_arguments("{count, plural, one {# item} other {# items}} for {name}"),
+            names(&["count", "name"])
+        );
+        assert_eq!(icu_arguments("{g, select, female {She} other {They}} left"), names(&["g"]));
+        assert_eq!(
+            icu_arguments("{gender, select, female {{name} ने अपना} other {{name} ने}} '{'quoted'}' {n, plural, offset:1 =1 {one} other {# {total, number}}}"),
+            names(&["gender", "n", "name", "total"])
+        );
+    }
+
+    #[test]
+    fn test_check() {
+        let catalog = Catalog::from_str(
+            r#"
+errors:
+  - code: ER-0007
+    detail_en_US: "fname: [fname] | lname: [lname] - [err]"
+    detail_hi_IN: "fname: [fname] | lname: [lname]"
+"#,
+        );
+        assert!(catalog.check("ER-0007", &names(&["fname", "lname"])).is_ok());
+        assert!(catalog.check("ER-0007", &names(&["fname", "lname", "err"])).is_err());
+        assert!(catalog.check("ER-0007", &names(&["fname"])).is_err());
+        assert!(catalog.check("ER-0040", &names(&[])).is_err());
+        assert!(catalog.check("ER-IO-NOTFOUND", &names(&[])).is_ok());
+        assert!(catalog.check("ER-JSONRPC-REMOTE", &names(&["code"])).is_ok());
+        assert!(catalog.check("ER-VALIDATION-MUST-MATCH", &names(&["field"])).is_err());
+        // typos of built-in codes aren't let through
+        assert!(catalog.check("ER-DB-NOTFUOND", &names(&[])).is_err());
+    }
+
+    #[test]
+    fn test_check_ftl() {
+        let catalog = Catalog::from_str(
+            r#"
+errors:
+  - code: ER-0008
+    detail_en_US: "User [id] not found"
+"#,
+        )
+        .with_ftl(
+            "en-US",
+            r#"
+# comments and terms aren't messages
+-brand = Acme
+
+ER-CART-LIMIT = { $count ->
+        [one] { $count } item is over the cart limit of { $limit }
+       *[other] { $count } items are over the cart limit of { $limit }
+    }
+    .title = Cart limit exceeded
+    .hint = Ask { $owner } for a bigger cart
+
+ER-0008 = User { $user_id } not found
+"#,
+        )
+        .with_ftl("hi-IN", "ER-0008 = उपयोगकर्ता { $user_id } नहीं मिला\n");
+        assert!(catalog.check("ER-CART-LIMIT", &names(&["count", "limit", "owner"])).is_ok());
+        assert!(catalog.check("ER-CART-LIMIT", &names(&["count", "limit"])).is_err());
+        // the ftl message wins over the yaml
+        assert!(catalog.check("ER-0008", &names(&["user_id"])).is_ok());
+        assert!(catalog.check("ER-0008", &names(&["id"])).is_err());
+        assert!(catalog.check("brand", &names(&[])).is_err());
+        assert!(catalog.check("ER-IO-NOTFOUND", &names(&[])).is_ok());
+    }
+}
@@ -0,0 +1,166 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{
+    spanned::Spanned, Attribute, Data, DeriveInput, Error, Fields, Ident, LitInt, LitStr, Result,
+};
+
+#[derive(Default)]
+struct ErrorAttr {
+    code: Option<LitStr>,
+    status: Option<LitInt>,
+}
+
+#[derive(Default)]
+struct FieldAttr {
+    source: bool,
+    skip: bool,
+}
+
+fn parse_error_attr(attrs: &[Attribute]) -> Result<ErrorAttr> {
+    let mut parsed = ErrorAttr::default();
+    for attr in attrs.iter().filter(|a| a.path().is_ident("standard_error")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("code") {
+                parsed.code = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("status") {
+                let status: LitInt = meta.value()?.parse()?;
+                match status.base10_parse::<u16>()? {
+                    100..=999 => parsed.status = Some(status),
+                    _ => return Err(Error::new(status.span(), "invalid http status")),
+                }
+            } else {
+                return Err(meta.error("expected `code` or `status`"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(parsed)
+}
+
+fn parse_field_attr(attrs: &[Attribute]) -> Result<FieldAttr> {
+    let mut parsed = FieldAttr::default();
+    for attr in attrs.iter().filter(|a| a.path().is_ident("standard_error")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("source") {
+                parsed.source = true;
+            } else if meta.path.is_ident("skip") {
+                parsed.skip = true;
+            } else {
+                return Err(meta.error("expected `source` or `skip`"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(parsed)
+}
+
+/// Destructuring pattern plus the statements building the StandardError for one variant/struct
+fn expand_fields(
+    fields: &Fields,
+    attr: ErrorAttr,
+    span: proc_macro2::Span,
+) -> Result<(TokenStream, TokenStream)> {
+    let code = attr
+        .code
+        .ok_or_else(|| Error::new(span, "missing #[standard_error(code = \"...\")]"))?;
+
+    // mixed-site, so fields named `values` or `err` aren't shadowed by the locals built here
+    let values_map = Ident::new("values", Span::mixed_site());
+    let err = Ident::new("err", Span::mixed_site());
+    let mut bindings = Vec::new();
+    let mut values = Vec::new();
+    let mut source = None;
+    for (i, field) in fields.iter().enumerate() {
+        let field_attr = parse_field_attr(&field.attrs)?;
+        let (binding, key) = match &field.ident {
+            Some(ident) => (ident.clone(), ident.to_string()),
+            None => (format_ident!("field{}", i), i.to_string()),
+        };
+        if field_attr.source {
+            if source.is_some() {
+                return Err(Error::new(field.span(), "only one field can be the source"));
+            }
+            values.push(quote! { #values_map.insert("err".to_string(), #binding.to_string()); });
+            source = Some(binding.clone());
+        } else if !field_attr.skip {
+            values.push(quote! { #values_map.insert(#key.to_string(), #binding.to_string()); });
+        }
+        bindings.push(match (&field.ident, field_attr.skip) {
+            (Some(ident), true) => quote! { #ident: _ },
+            (None, true) => quote! { _ },
+            (_, false) => quote! { #binding },
+        });
+    }
+
+    let pattern = match fields {
+        Fields::Named(_) => quote! { { #(#bindings),* } },
+        Fields::Unnamed(_) => quote! { ( #(#bindings),* ) },
+        Fields::Unit => quote! {},
+    };
+    let status = attr.status.map(|status| {
+        quote! {
+            let #err = ::standard_error::Status::code(
+                #err,
+                ::standard_error::StatusCode::from_u16(#status).expect("status checked by the derive"),
+            );
+        }
+    });
+    let source = source.map(|source| {
+        quote! { let #err = ::standard_error::Source::with_source(#err, #source); }
+    });
+    let body = quote! {
+        #[allow(unused_mut)]
+        let mut #values_map: ::std::collections::HashMap<String, String> = ::std::collections::HashMap::new();
+        #(#values)*
+        let #err = ::standard_error::StandardError::new(#code);
+        let #err = ::standard_error::Interpolate::interpolate_values(#err, #values_map);
+        #status
+        #source
+        #err
+    };
+    Ok((pattern, body))
+}
+
+pub fn expand(input: DeriveInput) -> Result<TokenStream> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let body = match &input.data {
+        Data::Enum(data) => {
+            let arms = data
+                .variants
+                .iter()
+                .map(|variant| {
+                    let attr = parse_error_attr(&variant.attrs)?;
+                    let ident: &Ident = &variant.ident;
+                    let (pattern, body) = expand_fields(&variant.fields, attr, variant.span())?;
+                    Ok(quote! { #name::#ident #pattern => { #body } })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            quote! {
+                match error {
+                    #(#arms)*
+                }
+            }
+        }
+        Data::Struct(data) => {
+            let attr = parse_error_attr(&input.attrs)?;
+            let (pattern, body) = expand_fields(&data.fields, attr, input.span())?;
+            quote! {
+                let #name #pattern = error;
+                #body
+            }
+        }
+        Data::Union(_) => {
+            return Err(Error::new(input.span(), "IntoStandardError can't be derived for unions"))
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::core::convert::From<#name #ty_generics> for ::standard_error::StandardError #where_clause {
+            fn from(error: #name #ty_generics) -> Self {
+                #body
+            }
+        }
+    })
+}
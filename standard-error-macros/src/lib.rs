@@ -0,0 +1,40 @@
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+mod catalog;
+mod derive;
+mod std_err;
+
+/// Generates `From<T> for StandardError`, see `#[standard_error(...)]` on each variant
+///
+/// ```ignore
+/// #[derive(Debug, IntoStandardError)]
+/// enum OrderError {
+///     #[standard_error(code = "ER-ORDER-NOTFOUND", status = 404)]
+///     NotFound { id: u64 },
+///     #[standard_error(code = "ER-ORDER-DB")]
+///     Db(#[standard_error(source)] diesel::result::Error),
+/// }
+/// ```
+#[proc_macro_derive(IntoStandardError, attributes(standard_error))]
+pub fn derive_into_standard_error(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    derive::expand(input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/// Builds a `StandardError` with named interpolation values and an optional status.
+/// When `errors.yaml` or an `ERROR_FTL_DIR` is around at build time, the code and its placeholders
+/// are checked against it
+///
+/// ```ignore
+/// std_err!("ER-0006", fname = user.first, lname = user.last; status = BAD_REQUEST)
+/// ```
+#[proc_macro]
+pub fn std_err(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as std_err::StdErrInput);
+    std_err::expand(input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
@@ -0,0 +1,115 @@
+use std::collections::BTreeSet;
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{
+    parse::{Parse, ParseStream},
+    Error, Expr, Ident, LitInt, LitStr, Result, Token,
+};
+
+use crate::catalog::Catalog;
+
+enum StatusArg {
+    Name(Ident),
+    Number(LitInt),
+}
+
+/// `"ER-0006", fname = user.first, lname = user.last; status = BAD_REQUEST`
+pub struct StdErrInput {
+    code: LitStr,
+    values: Vec<(Ident, Expr)>,
+    status: Option<StatusArg>,
+}
+
+impl Parse for StdErrInput {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let code: LitStr = input.parse()?;
+        let mut values = Vec::new();
+        while input.peek(Token![,]) {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() || input.peek(Token![;]) {
+                break;
+            }
+            let name: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            values.push((name, input.parse()?));
+        }
+
+        let mut status = None;
+        if input.peek(Token![;]) {
+            input.parse::<Token![;]>()?;
+            let key: Ident = input.parse()?;
+            if key != "status" {
+                return Err(Error::new(key.span(), "expected `status = ...`"));
+            }
+            input.parse::<Token![=]>()?;
+            status = Some(match input.peek(LitInt) {
+                true => {
+                    let status: LitInt = input.parse()?;
+                    match status.base10_parse::<u16>()? {
+                        100..=999 => StatusArg::Number(status),
+                        _ => return Err(Error::new(status.span(), "invalid http status")),
+                    }
+                }
+                false => StatusArg::Name(input.parse()?),
+            });
+            input.parse::<Option<Token![,]>>()?;
+        }
+        if !input.is_empty() {
+            return Err(input.error("unexpected tokens in std_err!"));
+        }
+        Ok(StdErrInput { code, values, status })
+    }
+}
+
+pub fn expand(input: StdErrInput) -> Result<TokenStream> {
+    let code = &input.code;
+
+    let mut names = BTreeSet::new();
+    for (name, _) in input.values.iter() {
+        if !names.insert(name.to_string()) {
+            return Err(Error::new(name.span(), format!("`{}` is given twice", name)));
+        }
+    }
+    // keeps the catalog as an input of the calling crate, so edits to the yaml or ftl are re-checked
+    let tracked = match Catalog::load() {
+        Some(catalog) => {
+            catalog
+                .check(&code.value(), &names)
+                .map_err(|e| Error::new(code.span(), e))?;
+            let paths = catalog.files.iter().map(|path| path.display().to_string());
+            Some(quote! { #(const _: &[u8] = include_bytes!(#paths);)* })
+        }
+        None => None,
+    };
+
+    // mixed-site, so the caller's expressions still see their own `values` and `err`
+    let values_map = Ident::new("values", Span::mixed_site());
+    let err = Ident::new("err", Span::mixed_site());
+    let values = input.values.iter().map(|(name, value)| {
+        let key = name.to_string();
+        quote! { #values_map.insert(#key.to_string(), ::std::string::ToString::to_string(&(#value))); }
+    });
+    let status = input.status.map(|status| {
+        let status = match status {
+            StatusArg::Name(name) => quote! { ::standard_error::StatusCode::#name },
+            StatusArg::Number(number) => quote! {
+                ::standard_error::StatusCode::from_u16(#number).expect("status checked by std_err!")
+            },
+        };
+        quote! { let #err = ::standard_error::Status::code(#err, #status); }
+    });
+
+    Ok(quote! {
+        {
+            #tracked
+            #[allow(unused_mut)]
+            let mut #values_map: ::std::collections::HashMap<String, String> = ::std::collections::HashMap::new();
+            #(#values)*
+            let #err = ::standard_error::StandardError::new(#code);
+            let #err = ::standard_error::Interpolate::interpolate_values(#err, #values_map);
+            #status
+            #err
+        }
+    })
+}